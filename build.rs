@@ -0,0 +1,194 @@
+//! Embeds the Trunk output in the server binary. Every file of `dist/`, or of
+//! `$DIST_DIR`, becomes an entry of `$OUT_DIR/assets.rs`, with gzip and brotli
+//! versions when the tools are installed and the file shrinks. The hashes of
+//! the inline scripts of its pages, the module Trunk starts the app with, go
+//! to `$OUT_DIR/inline_scripts.rs` for the Content-Security-Policy. Without
+//! a `dist/index.html` the server doesn't compile, unless `SERVER_WITHOUT_APP`
+//! is set for builds which only check the code.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+/// Already compressed formats gain nothing from another pass.
+const COMPRESSIBLE: [&str; 9] = [
+    "html",
+    "js",
+    "wasm",
+    "css",
+    "json",
+    "webmanifest",
+    "svg",
+    "txt",
+    "xml",
+];
+
+fn files(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files(&path, found);
+        } else {
+            found.push(path);
+        }
+    }
+}
+
+/// Runs `tool` with `args` and the file, keeping the output if it's smaller.
+fn compress(tool: &str, args: &[&str], file: &Path, size: usize, out: &Path) -> Option<PathBuf> {
+    let output = Command::new(tool).args(args).arg(file).output().ok()?;
+    if !output.status.success() || output.stdout.len() >= size {
+        return None;
+    }
+
+    fs::write(out, output.stdout).ok()?;
+    Some(out.to_path_buf())
+}
+
+/// FNV-1a, stable across builds unlike the hasher of the standard library.
+fn hash(content: &[u8]) -> u64 {
+    content.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The CSP sources of the `<script>` elements without a `src` in `html`.
+fn inline_scripts(html: &str, sources: &mut Vec<String>) {
+    let mut rest = html;
+    while let Some(start) = rest.find("<script") {
+        rest = &rest[start..];
+        let Some(open) = rest.find('>') else {
+            return;
+        };
+        let Some(close) = rest.find("</script>") else {
+            return;
+        };
+
+        if !rest[..open].contains(" src=") && open < close {
+            let digest = Sha256::digest(&rest[open + 1..close]);
+            let source = format!(
+                "'sha256-{}'",
+                base64::engine::general_purpose::STANDARD.encode(digest)
+            );
+            if !sources.contains(&source) {
+                sources.push(source);
+            }
+        }
+        rest = &rest[close..];
+    }
+}
+
+fn bytes(path: Option<&Path>) -> String {
+    match path {
+        Some(path) => format!("Some(include_bytes!({:?}))", path.display().to_string()),
+        None => "None".to_string(),
+    }
+}
+
+fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let dist = env::var("DIST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("dist"));
+    println!("cargo:rerun-if-env-changed=DIST_DIR");
+    println!("cargo:rerun-if-env-changed=SERVER_WITHOUT_APP");
+
+    // The web app itself never embeds its own output, which Trunk writes
+    // after every build of it.
+    if env::var("TARGET").unwrap().starts_with("wasm32") {
+        println!("cargo:rerun-if-changed=build.rs");
+        fs::write(out_dir.join("assets.rs"), "&[]").unwrap();
+        fs::write(out_dir.join("inline_scripts.rs"), "&[]").unwrap();
+        return;
+    }
+
+    // Watched even while missing, so the app is embedded once it's built.
+    println!("cargo:rerun-if-changed={}", dist.display());
+    if !dist.join("index.html").is_file() {
+        let assets = if env::var_os("SERVER_WITHOUT_APP").is_some() {
+            "&[]".to_string()
+        } else {
+            format!(
+                "compile_error!({:?})",
+                format!(
+                    "{} holds no index.html, build the app with `trunk build --release` first \
+                     or set SERVER_WITHOUT_APP to build the server without it",
+                    dist.display()
+                )
+            )
+        };
+        fs::write(out_dir.join("assets.rs"), assets).unwrap();
+        fs::write(out_dir.join("inline_scripts.rs"), "&[]").unwrap();
+        return;
+    }
+
+    let mut found = Vec::new();
+    files(&dist, &mut found);
+    found.sort();
+
+    let mut assets = String::from("&[\n");
+    let mut scripts = Vec::new();
+    for (index, file) in found.iter().enumerate() {
+        let Ok(content) = fs::read(file) else {
+            continue;
+        };
+        let path = file
+            .strip_prefix(&dist)
+            .unwrap()
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if path.ends_with(".html") {
+            inline_scripts(&String::from_utf8_lossy(&content), &mut scripts);
+        }
+        let compressible = file
+            .extension()
+            .is_some_and(|extension| COMPRESSIBLE.contains(&&*extension.to_string_lossy()));
+
+        let (gzip, brotli) = if compressible {
+            (
+                compress(
+                    "gzip",
+                    &["-9", "-n", "-c"],
+                    file,
+                    content.len(),
+                    &out_dir.join(format!("{index}.gz")),
+                ),
+                compress(
+                    "brotli",
+                    &["-q", "11", "-c"],
+                    file,
+                    content.len(),
+                    &out_dir.join(format!("{index}.br")),
+                ),
+            )
+        } else {
+            (None, None)
+        };
+
+        writeln!(
+            assets,
+            "    Asset {{ path: {:?}, content: include_bytes!({:?}), gzip: {}, brotli: {}, etag: \"{:016x}\" }},",
+            format!("/{path}"),
+            file.display().to_string(),
+            bytes(gzip.as_deref()),
+            bytes(brotli.as_deref()),
+            hash(&content)
+        )
+        .unwrap();
+    }
+    assets.push(']');
+
+    fs::write(out_dir.join("assets.rs"), assets).unwrap();
+    fs::write(out_dir.join("inline_scripts.rs"), format!("&{scripts:?}")).unwrap();
+}
@@ -1,119 +1,188 @@
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
 use log::debug;
 use serde_derive::{Deserialize, Serialize};
 
-use web_sys::Element;
 use yew::prelude::*;
 use yew::NodeRef;
 
 use gloo_storage::LocalStorage;
 use gloo_storage::Storage;
+use gloo_timers::callback::Timeout;
 
-use crate::utils::{highlighter, images::build_webp_url};
-use web_sys::HtmlInputElement;
+use crate::components::{
+    diff_preview::DiffPreview, print_pages, snippet_library::SnippetLibrary,
+    virtual_preview::VirtualPreview,
+};
+use crate::engine::annotations::{self, Annotations};
+use crate::engine::diff::Diff;
+use crate::engine::export::{self, docx, html, print, scene, ExportFormat};
+use crate::engine::languages::{ProgrammingLanguage, PROGRAMMING_LANGUAGES};
+use crate::engine::marks::{Emphasis, LineMarks};
+use crate::engine::redact::{self, Secret};
+use crate::engine::theme;
+use crate::engine::token::TokenStream;
+use crate::utils::history::{History, Session};
+use crate::utils::snippets::{self, Snippet};
+use crate::utils::workspace::{self, Bundle, ImportMode};
+use crate::utils::{canvas, clipboard, download, embed, images::build_webp_url};
+use web_sys::{HtmlElement, HtmlInputElement};
 
-const STATE_KEY: &str = "codestyle.state";
+pub const STATE_KEY: &str = "codestyle.state";
+/// Name of tabs which weren't opened from a file.
+const UNTITLED: &str = "untitled";
+/// Larger files are most likely not source code and would freeze the preview.
+const MAX_FILE_BYTES: f64 = 5.0 * 1024.0 * 1024.0;
+/// Milliseconds of no typing after which the code goes into the history.
+const SAVE_DELAY: u32 = 1000;
 
 pub struct App {
     state: State,
     textarea_ref: NodeRef,
+    compare_textarea_ref: NodeRef,
     webp_support: Option<bool>,
+    /// The highlighted code of every tab, `None` for tabs without code or language.
+    streams: Vec<Option<Rc<TokenStream>>>,
+    diff: Option<Rc<Diff>>,
+    ranges_spec: String,
+    range_error: Option<String>,
+    note_line: String,
+    note_text: String,
+    note_error: Option<String>,
+    /// Secrets found in the current code, one entry per match.
+    secrets: Vec<(Source, Secret)>,
+    snippets: Rc<Vec<Snippet>>,
+    show_library: bool,
+    history: History,
+    /// The tab whose typing isn't in the history yet, saved once the timer
+    /// fires, see [`App::remember_later`].
+    pending_save: Option<(usize, Timeout)>,
+    /// A read bundle waiting for the user to merge or replace, with the
+    /// names of its conflicting snippets.
+    pending_import: Option<(Bundle, Vec<String>)>,
+    workspace_status: Option<String>,
+    /// Whether a file is dragged over the editor.
+    dragging: bool,
+    /// The pages of the last print, shown by the print style sheet only.
+    print_pages: Option<Html>,
+    /// Opens the print dialog once the pages are rendered.
+    print_pending: bool,
+    /// Opened in an iframe of another page, see [`embed`].
+    embedded: bool,
 }
 
-#[derive(Deserialize, Serialize, Clone, Copy)]
-pub struct ProgrammingLanguage {
-    pub name: &'static str,
-    pub css_class: &'static str,
-    pub image_file: &'static str,
-    pub image_file_extension: &'static str,
+/// Icon of `language` in the language picker.
+fn language_image_url(language: &ProgrammingLanguage, webp_support: bool) -> String {
+    build_webp_url(
+        language.image_file,
+        language.image_file_extension,
+        webp_support,
+    )
 }
 
-impl ProgrammingLanguage {
-    fn to_img_url(self, webp_support: bool) -> String {
-        build_webp_url(self.image_file, self.image_file_extension, webp_support)
-    }
+/// One file of the session, with its own code, language and options.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(deserialize = "'de: 'static"))]
+pub struct Tab {
+    pub name: String,
+    pub code: String,
+    pub programming_language: Option<ProgrammingLanguage>,
+    pub marks: LineMarks,
+    pub annotations: Annotations,
+    /// `id` of the history session edits of this tab are recorded in.
+    pub session: f64,
 }
 
-const PROGRAMMING_LANGUAGES: [ProgrammingLanguage; 12] = [
-    ProgrammingLanguage {
-        name: "C / C++",
-        css_class: "cpp",
-        image_file: "images/cpp",
-        image_file_extension: "png",
-    },
-    ProgrammingLanguage {
-        name: "C#",
-        css_class: "csharp",
-        image_file: "images/csharp",
-        image_file_extension: "png",
-    },
-    ProgrammingLanguage {
-        name: "Python",
-        css_class: "python",
-        image_file: "images/python",
-        image_file_extension: "png",
-    },
-    ProgrammingLanguage {
-        name: "CSS",
-        css_class: "css",
-        image_file: "images/css",
-        image_file_extension: "png",
-    },
-    ProgrammingLanguage {
-        name: "Delphi",
-        css_class: "delphi",
-        image_file: "images/delphi",
-        image_file_extension: "png",
-    },
-    ProgrammingLanguage {
-        name: "VisualBasic",
-        css_class: "vb",
-        image_file: "images/viauslbasic",
-        image_file_extension: "svg",
-    },
-    ProgrammingLanguage {
-        name: "Java",
-        css_class: "java",
-        image_file: "images/java",
-        image_file_extension: "png",
-    },
-    ProgrammingLanguage {
-        name: "JavaScript",
-        css_class: "js",
-        image_file: "images/javascript",
-        image_file_extension: "png",
-    },
-    ProgrammingLanguage {
-        name: "Ruby",
-        css_class: "ruby",
-        image_file: "images/ruby",
-        image_file_extension: "png",
-    },
-    ProgrammingLanguage {
-        name: "SQL",
-        css_class: "sql",
-        image_file: "images/sql",
-        image_file_extension: "png",
-    },
-    ProgrammingLanguage {
-        name: "HTML / XML",
-        css_class: "xml",
-        image_file: "images/html",
-        image_file_extension: "png",
-    },
-    ProgrammingLanguage {
-        name: "PHP",
-        css_class: "php",
-        image_file: "images/php",
-        image_file_extension: "png",
-    },
-];
+impl Tab {
+    fn new(name: &str) -> Self {
+        Tab {
+            name: name.to_string(),
+            code: "".to_string(),
+            programming_language: None,
+            marks: LineMarks::default(),
+            annotations: Annotations::default(),
+            session: js_sys::Date::now(),
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 #[serde(bound(deserialize = "'de: 'static"))]
 pub struct State {
     pub show_info: bool,
-    pub code: String,
-    pub programming_language: Option<ProgrammingLanguage>,
+    /// Never empty, exported and previewed in this order.
+    pub tabs: Vec<Tab>,
+    /// Index of the tab shown in the editor.
+    pub active: usize,
+    pub compare: bool,
+    /// The changed version shown next to the active tab in compare mode.
+    pub compare_code: String,
+    /// What clicking a line number or applying a range marks lines as.
+    pub emphasis: Emphasis,
+    /// Masks detected secrets in the preview and every export.
+    pub redact: bool,
+    /// Matches of secrets the user chose to keep visible.
+    pub revealed: BTreeSet<Revealed>,
+    /// Numbers the lines of exported code, e.g. of images and Word documents.
+    pub line_numbers: bool,
+    /// Draws a title bar above exported images.
+    pub window_chrome: bool,
+    /// Draws Word documents in a shaded box.
+    pub shaded_box: bool,
+    /// Casts a shadow below exported images.
+    pub image_shadow: bool,
+    /// Pixels per px of PNG images, one of [`canvas::SCALES`].
+    pub image_scale: u32,
+    /// Puts the file names and page numbers above printed pages.
+    pub print_headers: bool,
+    /// Prints in shades of gray instead of colors.
+    pub print_grayscale: bool,
+}
+
+/// The code a secret was found in.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Source {
+    /// The tab at this index.
+    Tab(usize),
+    /// The changed version of compare mode.
+    Compared,
+}
+
+/// A match of a secret kept visible, by where it starts in its code. Its text
+/// is kept as well, so a secret moved there by an edit is masked again.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Revealed {
+    source: Source,
+    start: usize,
+    text: String,
+}
+
+impl Revealed {
+    fn of(source: Source, secret: &Secret) -> Self {
+        Revealed {
+            source,
+            start: secret.range.start,
+            text: secret.text.clone(),
+        }
+    }
+}
+
+impl State {
+    pub fn tab(&self) -> &Tab {
+        &self.tabs[self.active]
+    }
+
+    pub fn tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active]
+    }
+}
+
+/// Where a PNG image of the preview goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageTarget {
+    Download,
+    Clipboard,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -124,21 +193,526 @@ pub struct StoredState {
 pub enum Msg {
     HideInitMessage(bool),
     ChooseLanguage(&'static ProgrammingLanguage),
-    InputCode,
+    AddTab,
+    SelectTab(usize),
+    CloseTab(usize),
+    RenameTab(String),
+    /// Carries whether the input was a paste, which starts a new session.
+    InputCode(bool),
+    /// The tab whose typing paused, see [`App::remember_later`].
+    Remember(usize),
+    InputCompareCode,
+    ToggleCompare,
     WebPSupport(bool),
+    CopyFormatted,
+    Download(ExportFormat),
+    ToggleLineNumbers,
+    ToggleWindowChrome,
+    ToggleShadedBox,
+    SaveImage(ImageTarget),
+    ChooseImageScale(u32),
+    ToggleImageShadow,
+    Print,
+    TogglePrintHeaders,
+    TogglePrintGrayscale,
+    ChooseEmphasis(Emphasis),
+    InputRanges(String),
+    ApplyRanges,
+    /// The tab and the 1 based line whose gutter was clicked.
+    ToggleLine(usize, usize),
+    ClearMarks,
+    InputNoteLine(String),
+    InputNoteText(String),
+    AddNote,
+    /// The tab and the index of the note.
+    RemoveNote(usize, usize),
+    ToggleRedaction,
+    ToggleSecret(Revealed),
+    ToggleLibrary,
+    SnippetsLoaded(Vec<Snippet>),
+    SaveSnippet(String, Vec<String>),
+    OpenSnippet(Snippet),
+    DuplicateSnippet(Snippet),
+    DeleteSnippet(u32),
+    RestoreSession(Session),
+    ClearHistory,
+    ExportWorkspace,
+    ImportWorkspace(web_sys::File),
+    WorkspaceRead(String),
+    ConfirmImport(ImportMode),
+    CancelImport,
+    WorkspaceImported(String),
+    WorkspaceStatus(String),
+    DragOver(bool),
+    OpenFile(web_sys::File),
+    /// The name and content of an opened file.
+    FileRead(String, String),
+    /// A request of the embedding page and its origin.
+    Embed(embed::Request, String),
 }
 
 impl App {
-    fn format_code(&mut self) {
-        let window = web_sys::window().expect("no global `window` exists");
-        let document = window.document().expect("should have a document on window");
-        let elements = document.query_selector("div.dp-highlighter").unwrap();
+    fn highlight_code(&mut self) {
+        let brush = |tab: &Tab| {
+            tab.programming_language
+                .and_then(|language| crate::engine::brushes::find(language.css_class))
+        };
+
+        self.secrets.clear();
+
+        let tabs = self.state.tabs.clone();
+        self.streams = tabs
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| match brush(tab) {
+                Some(brush) if !self.state.compare && !tab.code.trim().is_empty() => {
+                    let stream = crate::engine::highlight(&tab.code, brush);
+                    Some(Rc::new(self.redact(stream, Source::Tab(index))))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let tab = &tabs[self.state.active];
+        self.diff = match brush(tab) {
+            Some(brush)
+                if self.state.compare
+                    && !(tab.code.trim().is_empty()
+                        && self.state.compare_code.trim().is_empty()) =>
+            {
+                let before = crate::engine::highlight(&tab.code, brush);
+                let after = crate::engine::highlight(&self.state.compare_code, brush);
+
+                let before = self.redact(before, Source::Tab(self.state.active));
+                let after = self.redact(after, Source::Compared);
+
+                Some(Rc::new(Diff::new(before, after)))
+            }
+            _ => None,
+        };
+    }
+
+    /// Records the secrets of `stream`, the code of `source`, and masks them
+    /// if redaction is on.
+    fn redact(&mut self, stream: TokenStream, source: Source) -> TokenStream {
+        let secrets = redact::detect(&stream);
+        let stream = if self.state.redact {
+            let revealed = secrets
+                .iter()
+                .filter(|secret| self.state.revealed.contains(&Revealed::of(source, secret)))
+                .map(|secret| secret.range.start)
+                .collect();
+            redact::mask(&stream, &secrets, &revealed)
+        } else {
+            stream
+        };
+
+        self.secrets
+            .extend(secrets.into_iter().map(|secret| (source, secret)));
+
+        stream
+    }
 
-        if let Some(element) = elements {
-            element.remove();
+    fn view_secrets(&self, ctx: &yew::Context<Self>) -> Html {
+        if self.secrets.is_empty() {
+            return html! {};
         }
 
-        highlighter::highlight();
+        html! {
+            <div class="d-flex flex-wrap align-items-center mb-2">
+                <button
+                    type="button"
+                    class={if self.state.redact {"btn btn-sm btn-danger me-2 mb-1"} else {"btn btn-sm btn-outline-danger me-2 mb-1"}}
+                    onclick={ctx.link().callback(|_| Msg::ToggleRedaction)}
+                >
+                    {format!("Redact secrets ({})", self.secrets.len())}
+                </button>
+                {
+                    self.secrets.iter().map(|(source, secret)| {
+                        let revealed = Revealed::of(*source, secret);
+                        let masked = self.state.redact && !self.state.revealed.contains(&revealed);
+
+                        html! {
+                            <button
+                                type="button"
+                                class={if masked {"btn btn-sm btn-dark me-1 mb-1"} else {"btn btn-sm btn-outline-dark me-1 mb-1"}}
+                                disabled={!self.state.redact}
+                                title={if masked {"Masked, click to show it"} else {"Shown, click to mask it"}}
+                                onclick={ctx.link().callback(move |_| Msg::ToggleSecret(revealed.clone()))}
+                            >
+                                {format!("{}: {} (line {})", secret.kind.name(), secret.hint(), secret.line)}
+                            </button>
+                        }
+                    }).collect::<Html>()
+                }
+            </div>
+        }
+    }
+
+    fn select_tab(&mut self, index: usize) {
+        self.state.active = index;
+        self.ranges_spec = self.state.tab().marks.to_spec(self.state.emphasis);
+        self.range_error = None;
+        self.note_line.clear();
+        self.note_text.clear();
+        self.note_error = None;
+        self.highlight_code();
+    }
+
+    /// Replaces the code and its options, e.g. with a saved snippet.
+    fn load(
+        &mut self,
+        language: Option<String>,
+        code: String,
+        marks: LineMarks,
+        annotations: Annotations,
+    ) {
+        let tab = self.state.tab_mut();
+        tab.code = code;
+        tab.programming_language =
+            language.and_then(|css_class| ProgrammingLanguage::by_css_class(&css_class));
+        tab.marks = marks;
+        tab.annotations = annotations;
+        self.state.compare = false;
+        self.ranges_spec = self.state.tab().marks.to_spec(self.state.emphasis);
+        self.range_error = None;
+        self.highlight_code();
+    }
+
+    /// Records the code and options of the tab at `index` in the session
+    /// history.
+    fn remember(&mut self, index: usize) {
+        if self
+            .pending_save
+            .as_ref()
+            .is_some_and(|(pending, _)| *pending == index)
+        {
+            self.pending_save = None;
+        }
+
+        let tab = &self.state.tabs[index];
+        // Code of the embedding page stays out of the history of this origin.
+        if self.embedded || tab.code.trim().is_empty() {
+            return;
+        }
+
+        self.history.record(Session {
+            id: tab.session,
+            updated: js_sys::Date::now(),
+            language: tab
+                .programming_language
+                .map(|language| language.css_class.to_string()),
+            code: tab.code.clone(),
+            marks: tab.marks.clone(),
+            annotations: tab.annotations.clone(),
+        });
+    }
+
+    /// Records the tab at `index` once the typing pauses, the history being
+    /// too large to store on every keystroke.
+    fn remember_later(&mut self, ctx: &yew::Context<Self>, index: usize) {
+        if self
+            .pending_save
+            .as_ref()
+            .is_some_and(|(pending, _)| *pending != index)
+        {
+            self.remember_pending();
+        }
+
+        let link = ctx.link().clone();
+        // Dropping the timer of the previous keystroke cancels it.
+        self.pending_save = Some((
+            index,
+            Timeout::new(SAVE_DELAY, move || link.send_message(Msg::Remember(index))),
+        ));
+    }
+
+    /// Records the typing a timer is still waiting for, e.g. before the tabs
+    /// change.
+    fn remember_pending(&mut self) {
+        if let Some((index, _)) = self.pending_save.take() {
+            self.remember(index);
+        }
+    }
+
+    /// Reloads the snippet library once `change` is stored.
+    fn store_snippets(
+        ctx: &yew::Context<Self>,
+        change: impl std::future::Future<Output = Result<(), wasm_bindgen::JsValue>> + 'static,
+    ) {
+        ctx.link().send_future(async move {
+            if let Err(error) = change.await {
+                debug!("Storing snippets failed: {:?}", error);
+            }
+
+            match snippets::load_all().await {
+                Ok(snippets) => Msg::SnippetsLoaded(snippets),
+                Err(error) => {
+                    debug!("Loading snippets failed: {:?}", error);
+                    Msg::SnippetsLoaded(Vec::new())
+                }
+            }
+        });
+    }
+
+    fn view_workspace(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <div class="workspace border-top pt-3 mt-3">
+                <h6>{"Workspace"}</h6>
+                <p class="text-sm text-muted mb-2">
+                    {"Snippets, recent sessions and settings as one file, to move them to another browser."}
+                </p>
+                <div class="d-flex align-items-center">
+                    <button
+                        type="button"
+                        class="btn btn-sm btn-outline-dark mb-0 me-2"
+                        onclick={ctx.link().callback(|_| Msg::ExportWorkspace)}
+                    >
+                        {"Export"}
+                    </button>
+                    <label class="btn btn-sm btn-outline-dark mb-0">
+                        {"Import"}
+                        <input
+                            type="file"
+                            accept=".json,application/json"
+                            hidden=true
+                            onchange={ctx.link().batch_callback(|e: Event| {
+                                let input: HtmlInputElement = e.target_unchecked_into();
+                                let file = input.files().and_then(|files| files.get(0));
+                                input.set_value("");
+                                file.map(Msg::ImportWorkspace)
+                            })}
+                        />
+                    </label>
+                </div>
+                if let Some((bundle, conflicts)) = &self.pending_import {
+                    <div class="alert alert-light text-sm mt-2 mb-0">
+                        {format!(
+                            "{} snippets and {} recent sessions.",
+                            bundle.snippets.len(),
+                            bundle.history.sessions().len()
+                        )}
+                        if !conflicts.is_empty() {
+                            <br />
+                            {format!("Differ from stored snippets of the same name: {}.", conflicts.join(", "))}
+                        }
+                        <div class="mt-2">
+                            <button type="button" class="btn btn-sm btn-dark mb-0 me-2" onclick={ctx.link().callback(|_| Msg::ConfirmImport(ImportMode::Merge))}>
+                                {"Merge"}
+                            </button>
+                            <button type="button" class="btn btn-sm btn-outline-danger mb-0 me-2" onclick={ctx.link().callback(|_| Msg::ConfirmImport(ImportMode::Replace))}>
+                                {"Replace"}
+                            </button>
+                            <button type="button" class="btn btn-sm btn-link mb-0" onclick={ctx.link().callback(|_| Msg::CancelImport)}>
+                                {"Cancel"}
+                            </button>
+                        </div>
+                    </div>
+                }
+                if let Some(status) = &self.workspace_status {
+                    <p class="text-sm mt-2 mb-0">{status.as_str()}</p>
+                }
+            </div>
+        }
+    }
+
+    fn export_options(&self, tab: &Tab) -> export::Options {
+        export::Options {
+            line_numbers: self.state.line_numbers,
+            marks: tab.marks.clone(),
+            annotations: tab.annotations.clone(),
+            ..export::Options::default()
+        }
+    }
+
+    /// The highlighted tabs in order, as exported.
+    fn files(&self) -> Vec<export::File<'_>> {
+        self.state
+            .tabs
+            .iter()
+            .zip(&self.streams)
+            .filter_map(|(tab, stream)| {
+                stream.as_ref().map(|stream| export::File {
+                    name: &tab.name,
+                    stream,
+                    options: self.export_options(tab),
+                })
+            })
+            .collect()
+    }
+
+    /// The highlighted code of the active tab.
+    fn stream(&self) -> Option<&Rc<TokenStream>> {
+        self.streams.get(self.state.active).and_then(Option::as_ref)
+    }
+
+    fn view_legend(&self, ctx: &yew::Context<Self>, tab: usize) -> Html {
+        let annotations = &self.state.tabs[tab].annotations;
+        if annotations.is_empty() {
+            return html! {};
+        }
+
+        html! {
+            <ul class="callouts">
+                {
+                    annotations.numbered().map(|(number, annotation)| {
+                        html! {
+                            <li>
+                                <span class="callout">{annotations::marker(number)}</span>
+                                <span class="note">{annotation.note.as_str()}</span>
+                                <button
+                                    type="button"
+                                    class="btn btn-link btn-sm text-danger p-0 mb-0"
+                                    title="Remove note"
+                                    onclick={ctx.link().callback(move |_| Msg::RemoveNote(tab, number - 1))}
+                                >
+                                    {"×"}
+                                </button>
+                            </li>
+                        }
+                    }).collect::<Html>()
+                }
+            </ul>
+        }
+    }
+
+    fn view_preview(&self, ctx: &yew::Context<Self>) -> Html {
+        if self.state.compare {
+            if let Some(diff) = &self.diff {
+                return html! { <DiffPreview diff={diff.clone()} /> };
+            }
+        } else if self.has_output() {
+            let multiple = self.state.tabs.len() > 1;
+
+            return self
+                .state
+                .tabs
+                .iter()
+                .zip(&self.streams)
+                .enumerate()
+                .filter_map(|(index, (tab, stream))| {
+                    stream.as_ref().map(|stream| {
+                        html! {
+                            <div key={index} class={classes!(multiple.then_some("file"))}>
+                                if multiple {
+                                    <div class="file-name">{tab.name.as_str()}</div>
+                                }
+                                <VirtualPreview
+                                    stream={stream.clone()}
+                                    marks={tab.marks.clone()}
+                                    annotations={tab.annotations.clone()}
+                                    on_gutter_click={ctx.link().callback(move |line| Msg::ToggleLine(index, line))}
+                                />
+                                {self.view_legend(ctx, index)}
+                            </div>
+                        }
+                    })
+                })
+                .collect::<Html>();
+        }
+
+        html! {
+            <pre class="w-100 h-100">{if !self.state.tab().code.trim().is_empty() {self.state.tab().code.as_str()} else {"Nothing to show...yet"}}</pre>
+        }
+    }
+
+    /// The preview in `format`, `None` if there is nothing to export.
+    fn export(&self, format: ExportFormat) -> Option<Vec<u8>> {
+        if self.state.compare {
+            let diff = self.diff.as_ref()?;
+            return match format {
+                ExportFormat::Html => Some(
+                    export::diff::html_table(diff, &theme::CLASSIC, &export::Options::default())
+                        .into_bytes(),
+                ),
+                ExportFormat::Rtf => Some(
+                    export::diff::rtf_table(diff, &theme::CLASSIC, &export::Options::default())
+                        .into_bytes(),
+                ),
+                _ => None,
+            };
+        }
+
+        let settings = export::Settings {
+            docx: docx::Layout {
+                boxed: self.state.shaded_box,
+            },
+            image: self.image_layout(),
+            ..export::Settings::default()
+        };
+        // Images show the active tab only.
+        let files = match format {
+            ExportFormat::Svg => self
+                .stream()
+                .map(|stream| export::File {
+                    name: &self.state.tab().name,
+                    stream,
+                    options: self.export_options(self.state.tab()),
+                })
+                .into_iter()
+                .collect(),
+            _ => self.files(),
+        };
+
+        export::render(format, &files, &theme::CLASSIC, &settings)
+    }
+
+    /// Shows the code the embedding page sent and renders it as asked.
+    fn embed(&mut self, request: embed::Highlight) -> Result<(ExportFormat, String), String> {
+        let language = match &request.language {
+            Some(alias) => ProgrammingLanguage::by_alias(alias)
+                .ok_or_else(|| format!("unknown language `{alias}`"))?,
+            None => ProgrammingLanguage::for_file("", &request.code)
+                .ok_or("the language could not be guessed, set `language`")?,
+        };
+        let theme = theme::find(request.theme.as_deref().unwrap_or("classic"))?;
+        let format = match &request.format {
+            Some(format) => format.parse::<ExportFormat>()?,
+            None => ExportFormat::Html,
+        };
+        if matches!(format, ExportFormat::Docx | ExportFormat::Odt) {
+            return Err(format!("`{}` is not a text format", format.extension()));
+        }
+
+        self.state.compare = false;
+        self.state.tab_mut().code = request.code;
+        self.state.tab_mut().programming_language = Some(language);
+        self.highlight_code();
+
+        let stream = self.stream().ok_or("there is no code to highlight")?;
+        let file = export::File {
+            name: &self.state.tab().name,
+            stream,
+            options: self.export_options(self.state.tab()),
+        };
+        let rendered = export::render(format, &[file], theme, &export::Settings::default())
+            .ok_or("there is no code to highlight")?;
+
+        Ok((format, String::from_utf8_lossy(&rendered).into_owned()))
+    }
+
+    fn image_layout(&self) -> scene::Layout {
+        scene::Layout {
+            chrome: self.state.window_chrome,
+            shadow: self.state.image_shadow,
+            ..scene::Layout::default()
+        }
+    }
+
+    /// Images show the active tab only and are named after it.
+    fn image_file_name(&self, extension: &str) -> String {
+        let name = self.state.tab().name.as_str();
+        let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+
+        format!("{stem}.{extension}")
+    }
+
+    fn has_output(&self) -> bool {
+        if self.state.compare {
+            self.diff.is_some()
+        } else {
+            self.streams.iter().any(Option::is_some)
+        }
     }
 }
 
@@ -147,20 +721,44 @@ impl Component for App {
     type Properties = ();
 
     fn create(ctx: &yew::Context<Self>) -> Self {
+        Self::store_snippets(ctx, async { Ok(()) });
+
         let mut state = State {
             show_info: true,
-            code: "".to_string(),
-            programming_language: None,
+            tabs: vec![Tab::new(UNTITLED)],
+            active: 0,
+            compare: false,
+            compare_code: "".to_string(),
+            emphasis: Emphasis::Highlighted,
+            redact: true,
+            revealed: BTreeSet::new(),
+            line_numbers: true,
+            window_chrome: true,
+            image_shadow: false,
+            image_scale: 2,
+            shaded_box: true,
+            print_headers: true,
+            print_grayscale: false,
         };
 
         let local_storage: Result<StoredState, _> = LocalStorage::get(STATE_KEY);
 
         if let Ok(stored_state) = local_storage {
-            if stored_state.show_info.is_some() {
-                state.show_info = stored_state.show_info.unwrap();
+            if let Some(show_info) = stored_state.show_info {
+                state.show_info = show_info;
             }
         }
 
+        // The page around decides what to show, not a stored preference.
+        let embedded = embed::is_embedded();
+        if embedded {
+            state.show_info = false;
+            embed::listen(
+                ctx.link()
+                    .callback(|(request, origin)| Msg::Embed(request, origin)),
+            );
+        }
+
         ctx.link().send_future(async {
             match crate::utils::images::has_webp_support().await {
                 true => Self::Message::WebPSupport(true),
@@ -171,19 +769,65 @@ impl Component for App {
         App {
             state,
             textarea_ref: NodeRef::default(),
+            compare_textarea_ref: NodeRef::default(),
             webp_support: None,
+            streams: Vec::new(),
+            diff: None,
+            ranges_spec: "".to_string(),
+            range_error: None,
+            note_line: "".to_string(),
+            note_text: "".to_string(),
+            note_error: None,
+            secrets: Vec::new(),
+            snippets: Rc::new(Vec::new()),
+            show_library: false,
+            history: History::load(),
+            pending_save: None,
+            pending_import: None,
+            workspace_status: None,
+            dragging: false,
+            print_pages: None,
+            print_pending: false,
+            embedded,
         }
     }
 
-    fn rendered(&mut self, _ctx: &yew::Context<Self>, _first_render: bool) {
-        debug!("Rendering code change");
-        if !self.state.code.trim().is_empty() && self.state.programming_language.is_some() {
-            self.format_code();
-        }
-    }
-
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            Msg::AddTab => {
+                self.remember_pending();
+                self.state.tabs.push(Tab::new(UNTITLED));
+                self.select_tab(self.state.tabs.len() - 1);
+                true
+            }
+            Msg::SelectTab(index) => {
+                self.remember_pending();
+                self.select_tab(index);
+                true
+            }
+            Msg::CloseTab(index) => {
+                self.remember_pending();
+                if self.state.tabs.len() == 1 {
+                    self.state.tabs[0] = Tab::new(UNTITLED);
+                } else {
+                    self.state.tabs.remove(index);
+                }
+                let active = if self.state.active > index {
+                    self.state.active - 1
+                } else {
+                    self.state.active.min(self.state.tabs.len() - 1)
+                };
+                self.select_tab(active);
+                true
+            }
+            Msg::RenameTab(name) => {
+                if let Some(language) = ProgrammingLanguage::by_extension(&name) {
+                    self.state.tab_mut().programming_language = Some(language);
+                }
+                self.state.tab_mut().name = name;
+                self.highlight_code();
+                true
+            }
             Msg::HideInitMessage(dont_show_again) => {
                 self.state.show_info = false;
 
@@ -207,15 +851,45 @@ impl Component for App {
             Msg::ChooseLanguage(language) => {
                 debug!("Selected {}", language.name);
 
-                self.state.programming_language = Some(*language);
+                self.state.tab_mut().programming_language = Some(*language);
+                self.highlight_code();
+                self.remember(self.state.active);
                 true
             }
-            Msg::InputCode => {
-                self.state.code = self
+            Msg::InputCode(pasted) => {
+                if pasted && !self.state.tab().code.trim().is_empty() {
+                    self.state.tab_mut().session = js_sys::Date::now();
+                }
+
+                self.state.tab_mut().code = self
                     .textarea_ref
                     .cast::<HtmlInputElement>()
                     .unwrap()
                     .value();
+                self.highlight_code();
+                if pasted {
+                    self.remember(self.state.active);
+                } else {
+                    self.remember_later(ctx, self.state.active);
+                }
+                true
+            }
+            Msg::Remember(index) => {
+                self.remember(index);
+                false
+            }
+            Msg::InputCompareCode => {
+                self.state.compare_code = self
+                    .compare_textarea_ref
+                    .cast::<HtmlInputElement>()
+                    .unwrap()
+                    .value();
+                self.highlight_code();
+                true
+            }
+            Msg::ToggleCompare => {
+                self.state.compare = !self.state.compare;
+                self.highlight_code();
                 true
             }
             Msg::WebPSupport(state) => {
@@ -223,6 +897,431 @@ impl Component for App {
 
                 true
             }
+            Msg::CopyFormatted => {
+                if self.state.compare {
+                    if let Some(diff) = &self.diff {
+                        let formatted = export::diff::html_table(
+                            diff,
+                            &theme::CLASSIC,
+                            &export::Options::default(),
+                        );
+
+                        if !clipboard::copy_rich_text(&formatted, &diff.after.text()) {
+                            debug!("Copying to the clipboard failed");
+                        }
+                    }
+                } else {
+                    let files = self.files();
+                    let (formatted, text) = match files.as_slice() {
+                        [] => return false,
+                        [file] => (
+                            html::render(file.stream, &theme::CLASSIC, &file.options),
+                            file.stream.text(),
+                        ),
+                        files => (
+                            html::render_files(files, &theme::CLASSIC),
+                            export::text(files),
+                        ),
+                    };
+
+                    if !clipboard::copy_rich_text(&formatted, &text) {
+                        debug!("Copying to the clipboard failed");
+                    }
+                }
+                false
+            }
+            Msg::Download(format) => {
+                if let Some(exported) = self.export(format) {
+                    let file_name = match format {
+                        _ if self.state.compare => format!("diff.{}", format.extension()),
+                        ExportFormat::Svg => self.image_file_name(format.extension()),
+                        _ => format!("code.{}", format.extension()),
+                    };
+
+                    download::download_bytes(&file_name, format.mime_type(), &exported);
+                }
+                false
+            }
+            Msg::ToggleLineNumbers => {
+                self.state.line_numbers = !self.state.line_numbers;
+                true
+            }
+            Msg::ToggleWindowChrome => {
+                self.state.window_chrome = !self.state.window_chrome;
+                true
+            }
+            Msg::ToggleShadedBox => {
+                self.state.shaded_box = !self.state.shaded_box;
+                true
+            }
+            Msg::SaveImage(target) => {
+                let Some(stream) = self.stream().filter(|_| !self.state.compare) else {
+                    return false;
+                };
+
+                let scene = scene::layout(
+                    stream,
+                    &theme::CLASSIC,
+                    &self.export_options(self.state.tab()),
+                    &self.image_layout(),
+                );
+                let file_name = self.image_file_name("png");
+                let scale = self.state.image_scale;
+
+                wasm_bindgen_futures::spawn_local(async move {
+                    let saved = async {
+                        let image = canvas::png(&canvas::draw(&scene, scale)?).await?;
+                        match target {
+                            ImageTarget::Download => download::download_blob(&file_name, &image),
+                            ImageTarget::Clipboard => clipboard::copy_image(&image).await?,
+                        }
+                        Ok::<_, wasm_bindgen::JsValue>(())
+                    };
+
+                    if let Err(error) = saved.await {
+                        debug!("Saving the image failed: {:?}", error);
+                    }
+                });
+                false
+            }
+            Msg::ChooseImageScale(scale) => {
+                self.state.image_scale = scale;
+                true
+            }
+            Msg::ToggleImageShadow => {
+                self.state.image_shadow = !self.state.image_shadow;
+                true
+            }
+            Msg::Print => {
+                let files = self.files();
+                if self.state.compare || files.is_empty() {
+                    return false;
+                }
+
+                let theme = if self.state.print_grayscale {
+                    &theme::GRAYSCALE
+                } else {
+                    &theme::CLASSIC
+                };
+                let layout = print::Layout {
+                    headers: self.state.print_headers,
+                    ..print::Layout::default()
+                };
+
+                print_pages::style(theme);
+                self.print_pages = Some(print_pages::view(&files, &layout));
+                self.print_pending = true;
+                true
+            }
+            Msg::TogglePrintHeaders => {
+                self.state.print_headers = !self.state.print_headers;
+                true
+            }
+            Msg::TogglePrintGrayscale => {
+                self.state.print_grayscale = !self.state.print_grayscale;
+                true
+            }
+            Msg::ChooseEmphasis(emphasis) => {
+                self.state.emphasis = emphasis;
+                self.ranges_spec = self.state.tab().marks.to_spec(emphasis);
+                self.range_error = None;
+                true
+            }
+            Msg::InputRanges(spec) => {
+                self.ranges_spec = spec;
+                self.range_error = None;
+                true
+            }
+            Msg::ApplyRanges => {
+                let emphasis = self.state.emphasis;
+                let lines = self.stream().map(|stream| stream.lines.len()).unwrap_or(0);
+                match self
+                    .state
+                    .tab_mut()
+                    .marks
+                    .replace(&self.ranges_spec, emphasis, lines)
+                {
+                    Ok(()) => self.ranges_spec = self.state.tab().marks.to_spec(emphasis),
+                    Err(error) => self.range_error = Some(error.to_string()),
+                }
+                self.remember(self.state.active);
+                true
+            }
+            Msg::ToggleLine(tab, line) => {
+                let emphasis = self.state.emphasis;
+                self.state.tabs[tab].marks.toggle(line, emphasis);
+                self.ranges_spec = self.state.tab().marks.to_spec(self.state.emphasis);
+                self.range_error = None;
+                self.remember(tab);
+                true
+            }
+            Msg::ClearMarks => {
+                self.state.tab_mut().marks.clear();
+                self.ranges_spec.clear();
+                self.range_error = None;
+                self.remember(self.state.active);
+                true
+            }
+            Msg::InputNoteLine(line) => {
+                self.note_line = line;
+                self.note_error = None;
+                true
+            }
+            Msg::InputNoteText(text) => {
+                self.note_text = text;
+                self.note_error = None;
+                true
+            }
+            Msg::AddNote => {
+                let lines = self.stream().map(|stream| stream.lines.len()).unwrap_or(0);
+
+                match self.note_line.trim().parse::<usize>() {
+                    Ok(line) if (1..=lines).contains(&line) => {
+                        if self.note_text.trim().is_empty() {
+                            self.note_error = Some("the note is empty".to_string());
+                        } else {
+                            self.state.tab_mut().annotations.add(line, &self.note_text);
+                            self.note_line.clear();
+                            self.note_text.clear();
+                            self.remember(self.state.active);
+                        }
+                    }
+                    _ => {
+                        self.note_error = Some(format!(
+                            "`{}` is not a line between 1 and {lines}",
+                            self.note_line.trim()
+                        ))
+                    }
+                }
+                true
+            }
+            Msg::RemoveNote(tab, index) => {
+                self.state.tabs[tab].annotations.remove(index);
+                self.remember(tab);
+                true
+            }
+            Msg::ToggleRedaction => {
+                self.state.redact = !self.state.redact;
+                self.highlight_code();
+                true
+            }
+            Msg::ToggleSecret(revealed) => {
+                if !self.state.revealed.remove(&revealed) {
+                    self.state.revealed.insert(revealed);
+                }
+                self.highlight_code();
+                true
+            }
+            Msg::ToggleLibrary => {
+                self.show_library = !self.show_library;
+                true
+            }
+            Msg::SnippetsLoaded(snippets) => {
+                self.snippets = Rc::new(snippets);
+                true
+            }
+            Msg::SaveSnippet(name, tags) => {
+                let now = js_sys::Date::now();
+                // Saving under an existing name updates that snippet.
+                let existing = self.snippets.iter().find(|snippet| snippet.name == name);
+                let snippet = Snippet {
+                    id: existing.and_then(|snippet| snippet.id),
+                    name,
+                    tags,
+                    language: self
+                        .state
+                        .tab()
+                        .programming_language
+                        .map(|language| language.css_class.to_string()),
+                    code: self.state.tab().code.clone(),
+                    marks: self.state.tab().marks.clone(),
+                    annotations: self.state.tab().annotations.clone(),
+                    created: existing.map(|snippet| snippet.created).unwrap_or(now),
+                    updated: now,
+                };
+
+                Self::store_snippets(
+                    ctx,
+                    async move { snippets::save(&snippet).await.map(|_| ()) },
+                );
+                false
+            }
+            Msg::OpenSnippet(snippet) => {
+                let tab = self.state.tab_mut();
+                tab.name = snippet.name;
+                tab.session = js_sys::Date::now();
+                self.load(
+                    snippet.language,
+                    snippet.code,
+                    snippet.marks,
+                    snippet.annotations,
+                );
+                self.remember(self.state.active);
+                true
+            }
+            Msg::DuplicateSnippet(snippet) => {
+                let copy = snippet.duplicate();
+
+                Self::store_snippets(ctx, async move { snippets::save(&copy).await.map(|_| ()) });
+                false
+            }
+            Msg::DeleteSnippet(id) => {
+                Self::store_snippets(ctx, snippets::delete(id));
+                false
+            }
+            Msg::RestoreSession(session) => {
+                self.state.tab_mut().session = session.id;
+                self.load(
+                    session.language,
+                    session.code,
+                    session.marks,
+                    session.annotations,
+                );
+                self.remember(self.state.active);
+                true
+            }
+            Msg::ClearHistory => {
+                self.pending_save = None;
+                self.history.clear();
+                true
+            }
+            Msg::ExportWorkspace => {
+                ctx.link().send_future(async {
+                    match workspace::export().await {
+                        Ok(json) => {
+                            download::download(
+                                "codestyle-workspace.json",
+                                "application/json",
+                                &json,
+                            );
+                            Msg::WorkspaceStatus("Workspace exported.".to_string())
+                        }
+                        Err(error) => Msg::WorkspaceStatus(format!("Export failed: {error:?}")),
+                    }
+                });
+                false
+            }
+            Msg::ImportWorkspace(file) => {
+                ctx.link().send_future(async move {
+                    match wasm_bindgen_futures::JsFuture::from(file.text()).await {
+                        Ok(text) => Msg::WorkspaceRead(text.as_string().unwrap_or_default()),
+                        Err(error) => {
+                            Msg::WorkspaceStatus(format!("Reading the file failed: {error:?}"))
+                        }
+                    }
+                });
+                false
+            }
+            Msg::WorkspaceRead(json) => {
+                match workspace::parse(&json) {
+                    Ok(bundle) => {
+                        let conflicts = workspace::conflicts(&bundle, &self.snippets);
+                        self.pending_import = Some((bundle, conflicts));
+                        self.workspace_status = None;
+                    }
+                    Err(error) => {
+                        self.pending_import = None;
+                        self.workspace_status = Some(format!("Import failed: {error}"));
+                    }
+                }
+                true
+            }
+            Msg::ConfirmImport(mode) => {
+                if let Some((bundle, _)) = self.pending_import.take() {
+                    ctx.link().send_future(async move {
+                        match workspace::import(bundle, mode).await {
+                            Ok(summary) => Msg::WorkspaceImported(summary),
+                            Err(error) => Msg::WorkspaceStatus(format!("Import failed: {error:?}")),
+                        }
+                    });
+                }
+                true
+            }
+            Msg::CancelImport => {
+                self.pending_import = None;
+                true
+            }
+            Msg::WorkspaceImported(summary) => {
+                self.workspace_status = Some(summary);
+                self.history = History::load();
+                Self::store_snippets(ctx, async { Ok(()) });
+                true
+            }
+            Msg::WorkspaceStatus(status) => {
+                self.workspace_status = Some(status);
+                true
+            }
+            Msg::DragOver(dragging) => {
+                let changed = self.dragging != dragging;
+                self.dragging = dragging;
+                changed
+            }
+            Msg::OpenFile(file) => {
+                if file.size() > MAX_FILE_BYTES {
+                    let _ = web_sys::window().unwrap().alert_with_message(&format!(
+                        "{} is too large to highlight, files up to {} MB can be opened.",
+                        file.name(),
+                        MAX_FILE_BYTES / 1024.0 / 1024.0
+                    ));
+                    return false;
+                }
+
+                ctx.link().send_future(async move {
+                    match wasm_bindgen_futures::JsFuture::from(file.text()).await {
+                        Ok(text) => {
+                            Msg::FileRead(file.name(), text.as_string().unwrap_or_default())
+                        }
+                        Err(error) => {
+                            debug!("Reading {} failed: {:?}", file.name(), error);
+                            Msg::DragOver(false)
+                        }
+                    }
+                });
+                false
+            }
+            Msg::Embed(embed::Request::Highlight(request), origin) => {
+                let id = request.id.clone();
+                let reply = match self.embed(request) {
+                    Ok((format, content)) => serde_json::json!({
+                        "type": "highlighted",
+                        "id": id,
+                        "format": format.extension(),
+                        "content": content,
+                    }),
+                    Err(message) => serde_json::json!({
+                        "type": "error",
+                        "id": id,
+                        "message": message,
+                    }),
+                };
+                embed::reply(&origin, &reply);
+                true
+            }
+            Msg::FileRead(name, code) => {
+                let language = ProgrammingLanguage::for_file(&name, &code);
+                debug!(
+                    "Opened {} as {:?}",
+                    name,
+                    language.map(|language| language.name)
+                );
+
+                if !self.state.tab().code.trim().is_empty() {
+                    self.state.tabs.push(Tab::new(&name));
+                    self.state.active = self.state.tabs.len() - 1;
+                } else {
+                    let tab = self.state.tab_mut();
+                    tab.name = name;
+                    tab.session = js_sys::Date::now();
+                }
+                self.load(
+                    language.map(|language| language.css_class.to_string()),
+                    code,
+                    LineMarks::default(),
+                    Annotations::default(),
+                );
+                self.remember(self.state.active);
+                true
+            }
         }
     }
 
@@ -230,10 +1329,10 @@ impl Component for App {
         debug!("rendered!");
 
         html! {
+            <>
             <header>
                 <div
-                    class="page-header min-vh-100"
-                    style="background-image: url(images/background.svg)"
+                    class="page-header min-vh-100 app-background"
                     loading="lazy"
                 >
                     {
@@ -244,12 +1343,7 @@ impl Component for App {
                                         <div class="card" data-animation="true">
                                             <div class="card-body">
                                                 <section
-                                                    class="py-9"
-                                                    style="
-                                                        padding: 16px !important;
-                                                        padding-bottom: 0 !important;
-                                                        padding-top: 16px !important;
-                                                    "
+                                                    class="py-9 intro"
                                                 >
                                                     {
                                                         html! {
@@ -299,9 +1393,8 @@ impl Component for App {
                                                                                 <br />
                                                                                 <button
                                                                                     type="button"
-                                                                                    class="btn btn-outline-danger"
+                                                                                    class="btn btn-outline-danger me-2"
                                                                                     onclick={ctx.link().callback(|_| Msg::HideInitMessage(true))}
-                                                                                    style="margin-right: 8px;"
                                                                                 >
                                                                                     {"Don't show me this again"}
                                                                                 </button>
@@ -329,10 +1422,28 @@ impl Component for App {
                     }
                     {
                         html! {
-                            <div class="container-fluid" style="height: 100vh;overflow-y: scroll" hidden={self.state.show_info}>
-                                <div class="row" style="min-height: 87.5%;margin-top:12.5vh;height: auto;">
+                            <div class="container-fluid app-workspace" hidden={self.state.show_info}>
+                                <div class="row app-workspace-row">
                                     <div class="col-md-6">
-                                        <div class="card" style="min-height: 75%;">
+                                        <div
+                                            class={classes!("card", "tall-card", self.dragging.then_some("dragging"))}
+                                            ondragover={ctx.link().callback(|e: DragEvent| {
+                                                e.prevent_default();
+                                                Msg::DragOver(true)
+                                            })}
+                                            ondragleave={ctx.link().callback(|_| Msg::DragOver(false))}
+                                            ondrop={ctx.link().batch_callback(|e: DragEvent| {
+                                                e.prevent_default();
+                                                let file = e
+                                                    .data_transfer()
+                                                    .and_then(|transfer| transfer.files())
+                                                    .and_then(|files| files.get(0));
+
+                                                std::iter::once(Msg::DragOver(false))
+                                                    .chain(file.map(Msg::OpenFile))
+                                                    .collect::<Vec<_>>()
+                                            })}
+                                        >
                                             <div class="card-body">
                                                 {
                                                     html!{
@@ -347,9 +1458,9 @@ impl Component for App {
                                                                                 id="navbarDropdownMenuLink2"
                                                                             >
                                                                                 {
-                                                                                    if let Some(programming_language) = self.state.programming_language {
+                                                                                    if let Some(programming_language) = self.state.tab().programming_language {
                                                                                         if let Some(webp_support) = self.webp_support {
-                                                                                            html!{<img src={programming_language.to_img_url(webp_support)}  height="24"/>}
+                                                                                            html!{<img src={language_image_url(&programming_language, webp_support)}  height="24"/>}
                                                                                         } else {
                                                                                             html!{}
                                                                                         }
@@ -359,7 +1470,7 @@ impl Component for App {
                                                                                 }
 
                                                                                 {
-                                                                                    if let Some(programming_language) = self.state.programming_language {
+                                                                                    if let Some(programming_language) = self.state.tab().programming_language {
                                                                                         format!("  {name}", name=programming_language.name)
                                                                                     } else {
                                                                                         "Select a Programming language ...".to_string()
@@ -376,7 +1487,7 @@ impl Component for App {
                                                                                             html! {
                                                                                                 <li onclick={ctx.link().callback(|_| Msg::ChooseLanguage(language))}>
                                                                                                     <a class="dropdown-item" href="#">
-                                                                                                        <img src={language.to_img_url(webp_support)} height="24" />
+                                                                                                        <img src={language_image_url(language, webp_support)} height="24" />
                                                                                                         {"   "}{language.name}
                                                                                                     </a>
                                                                                                 </li>
@@ -389,25 +1500,271 @@ impl Component for App {
                                                                             </ul>
                                                                         </div>
                                                                     </div>
-                                                                    <div class="col-md-4 col-lg-5" style="padding-right:0;">
+                                                                    <div class="col-md-4 col-lg-5 pe-0">
                                                                         <div class="text-right">
+                                                                            <label class="btn btn-outline-dark me-2" title="Open a file, or drop one on the editor">
+                                                                                {"Open"}
+                                                                                <input
+                                                                                    type="file"
+                                                                                    hidden=true
+                                                                                    onchange={ctx.link().batch_callback(|e: Event| {
+                                                                                        let input: HtmlInputElement = e.target_unchecked_into();
+                                                                                        let file = input.files().and_then(|files| files.get(0));
+                                                                                        input.set_value("");
+                                                                                        file.map(Msg::OpenFile)
+                                                                                    })}
+                                                                                />
+                                                                            </label>
+                                                                            <div class="dropdown d-inline-block me-2">
+                                                                                <button
+                                                                                    type="button"
+                                                                                    class="btn btn-outline-dark dropdown-toggle"
+                                                                                    data-bs-toggle="dropdown"
+                                                                                    disabled={self.history.is_empty()}
+                                                                                >
+                                                                                    {"Recent"}
+                                                                                </button>
+                                                                                <ul class="dropdown-menu">
+                                                                                    {
+                                                                                        self.history.sessions().iter().map(|session| {
+                                                                                            let restored = session.clone();
+                                                                                            let updated = js_sys::Date::new(&session.updated.into())
+                                                                                                .to_locale_string("default", &Default::default());
+                                                                                            html! {
+                                                                                                <li onclick={ctx.link().callback(move |_| Msg::RestoreSession(restored.clone()))}>
+                                                                                                    <a class="dropdown-item" href="#">
+                                                                                                        <span class={classes!((session.id == self.state.tab().session).then_some("fw-bold"))}>{session.title()}</span>
+                                                                                                        <br />
+                                                                                                        <small class="text-muted">
+                                                                                                            {format!("{} · {}", session.language.as_deref().unwrap_or("plain text"), String::from(updated))}
+                                                                                                        </small>
+                                                                                                    </a>
+                                                                                                </li>
+                                                                                            }
+                                                                                        }).collect::<Html>()
+                                                                                    }
+                                                                                    <li><hr class="dropdown-divider" /></li>
+                                                                                    <li onclick={ctx.link().callback(|_| Msg::ClearHistory)}>
+                                                                                        <a class="dropdown-item text-danger" href="#">{"Clear history"}</a>
+                                                                                    </li>
+                                                                                </ul>
+                                                                            </div>
+                                                                            <button
+                                                                                type="button"
+                                                                                class={if self.show_library {"btn btn-dark me-2"} else {"btn btn-outline-dark me-2"}}
+                                                                                onclick={ctx.link().callback(|_| Msg::ToggleLibrary)}
+                                                                            >
+                                                                                {"Snippets"}
+                                                                            </button>
+                                                                            <button
+                                                                                type="button"
+                                                                                class={if self.state.compare {"btn btn-dark me-2"} else {"btn btn-outline-dark me-2"}}
+                                                                                onclick={ctx.link().callback(|_| Msg::ToggleCompare)}
+                                                                            >
+                                                                                {"Compare"}
+                                                                            </button>
+                                                                            <button
+                                                                                type="button"
+                                                                                class="btn btn-outline-dark me-2"
+                                                                                disabled={!self.has_output()}
+                                                                                onclick={ctx.link().callback(|_| Msg::CopyFormatted)}
+                                                                            >
+                                                                                {"Copy"}
+                                                                            </button>
+                                                                            <div class="dropdown d-inline-block">
+                                                                                <button
+                                                                                    type="button"
+                                                                                    class="btn btn-outline-dark dropdown-toggle"
+                                                                                    data-bs-toggle="dropdown"
+                                                                                    data-bs-auto-close="outside"
+                                                                                    disabled={!self.has_output()}
+                                                                                >
+                                                                                    {"Export"}
+                                                                                </button>
+                                                                                <ul class="dropdown-menu">
+                                                                                    {
+                                                                                        ExportFormat::ALL.iter().map(|&format| {
+                                                                                            let disabled = self.state.compare && !format.supports_diff();
+                                                                                            html! {
+                                                                                                <li onclick={ctx.link().callback(move |_| Msg::Download(format))}>
+                                                                                                    <a class={classes!("dropdown-item", disabled.then_some("disabled"))} href="#">{format.name()}</a>
+                                                                                                </li>
+                                                                                            }
+                                                                                        }).collect::<Html>()
+                                                                                    }
+                                                                                    <li onclick={ctx.link().callback(|_| Msg::SaveImage(ImageTarget::Download))}>
+                                                                                        <a class={classes!("dropdown-item", self.state.compare.then_some("disabled"))} href="#">{"PNG image"}</a>
+                                                                                    </li>
+                                                                                    <li onclick={ctx.link().callback(|_| Msg::SaveImage(ImageTarget::Clipboard))}>
+                                                                                        <a class={classes!("dropdown-item", self.state.compare.then_some("disabled"))} href="#">{"Copy as PNG"}</a>
+                                                                                    </li>
+                                                                                    <li onclick={ctx.link().callback(|_| Msg::Print)}>
+                                                                                        <a class={classes!("dropdown-item", self.state.compare.then_some("disabled"))} href="#">{"Print…"}</a>
+                                                                                    </li>
+                                                                                    <li><hr class="dropdown-divider" /></li>
+                                                                                    <li class="dropdown-item-text">
+                                                                                        <span class="me-2">{"Scale"}</span>
+                                                                                        <div class="btn-group btn-group-sm">
+                                                                                            {
+                                                                                                canvas::SCALES.iter().map(|&scale| html! {
+                                                                                                    <button
+                                                                                                        type="button"
+                                                                                                        class={if scale == self.state.image_scale {"btn btn-dark mb-0"} else {"btn btn-outline-dark mb-0"}}
+                                                                                                        onclick={ctx.link().callback(move |_| Msg::ChooseImageScale(scale))}
+                                                                                                    >
+                                                                                                        {format!("{scale}x")}
+                                                                                                    </button>
+                                                                                                }).collect::<Html>()
+                                                                                            }
+                                                                                        </div>
+                                                                                    </li>
+                                                                                    <li>
+                                                                                        <label class="dropdown-item mb-0">
+                                                                                            <input
+                                                                                                type="checkbox"
+                                                                                                class="me-2"
+                                                                                                checked={self.state.line_numbers}
+                                                                                                onchange={ctx.link().callback(|_| Msg::ToggleLineNumbers)}
+                                                                                            />
+                                                                                            {"Line numbers"}
+                                                                                        </label>
+                                                                                    </li>
+                                                                                    <li>
+                                                                                        <label class="dropdown-item mb-0">
+                                                                                            <input
+                                                                                                type="checkbox"
+                                                                                                class="me-2"
+                                                                                                checked={self.state.shaded_box}
+                                                                                                onchange={ctx.link().callback(|_| Msg::ToggleShadedBox)}
+                                                                                            />
+                                                                                            {"Shaded box in Word"}
+                                                                                        </label>
+                                                                                    </li>
+                                                                                    <li>
+                                                                                        <label class="dropdown-item mb-0">
+                                                                                            <input
+                                                                                                type="checkbox"
+                                                                                                class="me-2"
+                                                                                                checked={self.state.window_chrome}
+                                                                                                onchange={ctx.link().callback(|_| Msg::ToggleWindowChrome)}
+                                                                                            />
+                                                                                            {"Window chrome"}
+                                                                                        </label>
+                                                                                    </li>
+                                                                                    <li>
+                                                                                        <label class="dropdown-item mb-0">
+                                                                                            <input
+                                                                                                type="checkbox"
+                                                                                                class="me-2"
+                                                                                                checked={self.state.image_shadow}
+                                                                                                onchange={ctx.link().callback(|_| Msg::ToggleImageShadow)}
+                                                                                            />
+                                                                                            {"Drop shadow"}
+                                                                                        </label>
+                                                                                    </li>
+                                                                                    <li>
+                                                                                        <label class="dropdown-item mb-0">
+                                                                                            <input
+                                                                                                type="checkbox"
+                                                                                                class="me-2"
+                                                                                                checked={self.state.print_headers}
+                                                                                                onchange={ctx.link().callback(|_| Msg::TogglePrintHeaders)}
+                                                                                            />
+                                                                                            {"Page headers"}
+                                                                                        </label>
+                                                                                    </li>
+                                                                                    <li>
+                                                                                        <label class="dropdown-item mb-0">
+                                                                                            <input
+                                                                                                type="checkbox"
+                                                                                                class="me-2"
+                                                                                                checked={self.state.print_grayscale}
+                                                                                                onchange={ctx.link().callback(|_| Msg::TogglePrintGrayscale)}
+                                                                                            />
+                                                                                            {"Print in grayscale"}
+                                                                                        </label>
+                                                                                    </li>
+                                                                                </ul>
+                                                                            </div>
                                                                         </div>
                                                                     </div>
                                                                 </div>
-                                                                <div class="row" style="height: 100%;">
-                                                                    <div class="col-12" style="padding-right: 8px;height: 100%;">
-                                                                        <div class="input-group-outline input-group" style="height: 100%;">
+                                                                <ul class="nav nav-tabs file-tabs mt-2">
+                                                                    {
+                                                                        self.state.tabs.iter().enumerate().map(|(index, tab)| {
+                                                                            let active = index == self.state.active;
+                                                                            html! {
+                                                                                <li class="nav-item" key={index}>
+                                                                                    <span
+                                                                                        class={classes!("nav-link", active.then_some("active"))}
+                                                                                        onclick={ctx.link().callback(move |_| Msg::SelectTab(index))}
+                                                                                    >
+                                                                                        if active {
+                                                                                            <input
+                                                                                                type="text"
+                                                                                                class="tab-name"
+                                                                                                size={tab.name.chars().count().max(8).to_string()}
+                                                                                                value={tab.name.clone()}
+                                                                                                oninput={ctx.link().callback(|e: InputEvent| Msg::RenameTab(e.target_unchecked_into::<HtmlInputElement>().value()))}
+                                                                                            />
+                                                                                        } else {
+                                                                                            {tab.name.as_str()}
+                                                                                        }
+                                                                                        <button
+                                                                                            type="button"
+                                                                                            class="close-tab"
+                                                                                            title="Close"
+                                                                                            onclick={ctx.link().callback(move |e: MouseEvent| {
+                                                                                                e.stop_propagation();
+                                                                                                Msg::CloseTab(index)
+                                                                                            })}
+                                                                                        >
+                                                                                            {"×"}
+                                                                                        </button>
+                                                                                    </span>
+                                                                                </li>
+                                                                            }
+                                                                        }).collect::<Html>()
+                                                                    }
+                                                                    <li class="nav-item">
+                                                                        <span class="nav-link" title="New tab" onclick={ctx.link().callback(|_| Msg::AddTab)}>{"+"}</span>
+                                                                    </li>
+                                                                </ul>
+                                                                <div class="row h-100">
+                                                                    <div class={classes!(if self.state.compare {"col-6"} else {"col-12"}, "pe-2", "h-100")}>
+                                                                        <div class="input-group-outline input-group h-100">
                                                                             <textarea
                                                                                 ref={self.textarea_ref.clone()}
                                                                                 name="message"
-                                                                                class="form-control"
+                                                                                class="form-control code-input"
                                                                                 id="message"
-                                                                                style={ format!("min-height: calc(75vh - 204px);overflow-y: hidden;{height}", height=if let Some(element) = self.textarea_ref.cast::<Element>(){format!("height: {px}px", px=element.scroll_height())} else {"".to_string()}) }
-                                                                                oninput={ctx.link().callback(|_e| Msg::InputCode)}
-                                                                                placeholder="Just paste something and see what happens...."
+                                                                                value={self.state.tab().code.clone()}
+                                                                                oninput={ctx.link().callback(|e: InputEvent| Msg::InputCode(e.input_type() == "insertFromPaste"))}
+                                                                                placeholder={if self.state.compare {"Paste the original version..."} else {"Just paste something and see what happens...."}}
                                                                             ></textarea>
                                                                         </div>
                                                                     </div>
+                                                                    {
+                                                                        if self.state.compare {
+                                                                            html! {
+                                                                                <div class="col-6 pe-2 h-100">
+                                                                                    <div class="input-group-outline input-group h-100">
+                                                                                        <textarea
+                                                                                            ref={self.compare_textarea_ref.clone()}
+                                                                                            name="compare"
+                                                                                            class="form-control code-input"
+                                                                                            id="compare"
+                                                                                            value={self.state.compare_code.clone()}
+                                                                                            oninput={ctx.link().callback(|_e| Msg::InputCompareCode)}
+                                                                                            placeholder="...and the changed one here"
+                                                                                        ></textarea>
+                                                                                    </div>
+                                                                                </div>
+                                                                            }
+                                                                        } else {
+                                                                            html! {}
+                                                                        }
+                                                                    }
                                                                 </div>
                                                             </div>
                                                         </div>
@@ -419,9 +1776,77 @@ impl Component for App {
                                     {
                                         html! {
                                             <div class="col-md-6">
-                                                <div class="card" style="min-height: 75%;">
+                                                <div class="card tall-card">
                                                     <div class="card-body">
-                                                        <pre name="code" style="width:100%;height:100%" class={ if let Some(language) = self.state.programming_language { language.css_class } else {""}}>{if !self.state.code.trim().is_empty() {self.state.code.as_str()} else {"Nothing to show...yet"}}</pre>
+                                                        <div class="d-flex align-items-center mb-2" hidden={self.state.compare}>
+                                                            <div class="btn-group me-2 mb-0" role="group">
+                                                                {
+                                                                    Emphasis::ALL.iter().map(|emphasis| {
+                                                                        let emphasis = *emphasis;
+                                                                        html! {
+                                                                            <button
+                                                                                type="button"
+                                                                                class={if self.state.emphasis == emphasis {"btn btn-sm btn-dark mb-0"} else {"btn btn-sm btn-outline-dark mb-0"}}
+                                                                                onclick={ctx.link().callback(move |_| Msg::ChooseEmphasis(emphasis))}
+                                                                            >
+                                                                                {emphasis.name()}
+                                                                            </button>
+                                                                        }
+                                                                    }).collect::<Html>()
+                                                                }
+                                                            </div>
+                                                            <input
+                                                                type="text"
+                                                                class={classes!("form-control", "form-control-sm", self.range_error.is_some().then_some("is-invalid"))}
+                                                                placeholder="Lines, e.g. 3,7-9"
+                                                                title={self.range_error.clone().unwrap_or_default()}
+                                                                value={self.ranges_spec.clone()}
+                                                                oninput={ctx.link().callback(|e: InputEvent| Msg::InputRanges(e.target_unchecked_into::<HtmlInputElement>().value()))}
+                                                            />
+                                                            <button
+                                                                type="button"
+                                                                class="btn btn-sm btn-outline-dark ms-2 mb-0"
+                                                                onclick={ctx.link().callback(|_| Msg::ApplyRanges)}
+                                                            >
+                                                                {"Mark"}
+                                                            </button>
+                                                            <button
+                                                                type="button"
+                                                                class="btn btn-sm btn-outline-danger ms-2 mb-0"
+                                                                disabled={self.state.tab().marks.is_empty()}
+                                                                onclick={ctx.link().callback(|_| Msg::ClearMarks)}
+                                                            >
+                                                                {"Clear"}
+                                                            </button>
+                                                        </div>
+                                                        <div class="d-flex align-items-center mb-2" hidden={self.state.compare}>
+                                                            <input
+                                                                type="number"
+                                                                min="1"
+                                                                class={classes!("form-control", "form-control-sm", "line-input", self.note_error.is_some().then_some("is-invalid"))}
+                                                                placeholder="Line"
+                                                                title={self.note_error.clone().unwrap_or_default()}
+                                                                value={self.note_line.clone()}
+                                                                oninput={ctx.link().callback(|e: InputEvent| Msg::InputNoteLine(e.target_unchecked_into::<HtmlInputElement>().value()))}
+                                                            />
+                                                            <input
+                                                                type="text"
+                                                                class="form-control form-control-sm ms-2"
+                                                                placeholder="Note"
+                                                                value={self.note_text.clone()}
+                                                                oninput={ctx.link().callback(|e: InputEvent| Msg::InputNoteText(e.target_unchecked_into::<HtmlInputElement>().value()))}
+                                                            />
+                                                            <button
+                                                                type="button"
+                                                                class="btn btn-sm btn-outline-dark ms-2 mb-0"
+                                                                disabled={self.stream().is_none()}
+                                                                onclick={ctx.link().callback(|_| Msg::AddNote)}
+                                                            >
+                                                                {"Note"}
+                                                            </button>
+                                                        </div>
+                                                        {self.view_secrets(ctx)}
+                                                        {self.view_preview(ctx)}
                                                     </div>
                                                 </div>
                                             </div>
@@ -432,7 +1857,40 @@ impl Component for App {
                         }
                     }
                 </div>
+                if self.show_library {
+                    <SnippetLibrary
+                        snippets={self.snippets.clone()}
+                        can_save={!self.state.tab().code.trim().is_empty()}
+                        on_save={ctx.link().callback(|(name, tags)| Msg::SaveSnippet(name, tags))}
+                        on_open={ctx.link().callback(Msg::OpenSnippet)}
+                        on_duplicate={ctx.link().callback(Msg::DuplicateSnippet)}
+                        on_delete={ctx.link().callback(Msg::DeleteSnippet)}
+                        on_close={ctx.link().callback(|_| Msg::ToggleLibrary)}
+                    >
+                        {self.view_workspace(ctx)}
+                    </SnippetLibrary>
+                }
             </header>
+            if let Some(pages) = &self.print_pages {
+                <div class="print-output">{pages.clone()}</div>
+            }
+            </>
+        }
+    }
+
+    fn rendered(&mut self, _ctx: &yew::Context<Self>, _first_render: bool) {
+        // Grows the editors with their code. Through the CSSOM, since the
+        // Content-Security-Policy refuses style attributes.
+        for textarea in [&self.textarea_ref, &self.compare_textarea_ref] {
+            if let Some(element) = textarea.cast::<HtmlElement>() {
+                let height = format!("{}px", element.scroll_height());
+                let _ = element.style().set_property("height", &height);
+            }
+        }
+        if std::mem::take(&mut self.print_pending) {
+            if let Err(error) = web_sys::window().unwrap().print() {
+                debug!("Printing failed: {:?}", error);
+            }
         }
     }
 }
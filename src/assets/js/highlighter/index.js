@@ -1,5 +0,0 @@
-// This file is included in `bindings.rs`
-
-export const highlight = () => {
-  dp.SyntaxHighlighter.HighlightAll("code", false);
-};
@@ -0,0 +1,280 @@
+//! The engine for scripts, exported next to the element:
+//!
+//! ```js
+//! import init, { highlight, languages } from "/code-highlight.js";
+//!
+//! await init();
+//! const result = highlight("SELECT 1", "sql", { lineNumbers: false });
+//! result.toHtml();
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde_derive::Deserialize;
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use planetb_syntax_highligher::engine::export::{self, html, rtf};
+use planetb_syntax_highligher::engine::languages::{self, ProgrammingLanguage};
+use planetb_syntax_highligher::engine::marks::LineMarks;
+use planetb_syntax_highligher::engine::theme::{self, Theme};
+use planetb_syntax_highligher::engine::token::TokenStream;
+use planetb_syntax_highligher::engine::{brushes, detect};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &str = r#"
+export interface HighlightOptions {
+    /** `classic` or `grayscale`, `classic` if missing. */
+    theme?: string;
+    /** Numbers the lines, which is the default. */
+    lineNumbers?: boolean;
+    /** The number of the first line, 1 if missing. */
+    firstLine?: number;
+    /** Line ranges such as `3,7-9` by emphasis, e.g. `{ highlighted: "1" }`. */
+    marks?: Record<string, string>;
+}
+
+export interface Token {
+    /** The class of the token in the preview, e.g. `keyword`, `plain` for text. */
+    kind: string;
+    text: string;
+}
+
+export interface Language {
+    id: string;
+    name: string;
+    aliases: string[];
+    extensions: string[];
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "HighlightOptions")]
+    pub type HighlightOptionsObject;
+
+    #[wasm_bindgen(typescript_type = "Token[][]")]
+    pub type Tokens;
+
+    #[wasm_bindgen(typescript_type = "Language[]")]
+    pub type Languages;
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+struct HighlightOptions {
+    theme: Option<String>,
+    line_numbers: bool,
+    first_line: usize,
+    marks: BTreeMap<String, String>,
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        HighlightOptions {
+            theme: None,
+            line_numbers: true,
+            first_line: 1,
+            marks: BTreeMap::new(),
+        }
+    }
+}
+
+impl HighlightOptions {
+    /// The options from their JSON, the defaults without any.
+    fn parse(json: Option<&str>) -> Result<Self, String> {
+        match json {
+            Some(json) => {
+                serde_json::from_str(json).map_err(|error| format!("invalid options: {error}"))
+            }
+            None => Ok(HighlightOptions::default()),
+        }
+    }
+
+    fn theme(&self) -> Result<&'static Theme, String> {
+        theme::find(self.theme.as_deref().unwrap_or("classic"))
+    }
+
+    /// The export options for code of `lines` lines.
+    fn export(&self, lines: usize) -> Result<export::Options, String> {
+        if self.first_line.checked_add(lines).is_none() {
+            return Err(format!("the first line {} is too large", self.first_line));
+        }
+        let marks = LineMarks::from_specs(&self.marks, lines)?;
+
+        Ok(export::Options {
+            line_numbers: self.line_numbers,
+            first_line: self.first_line,
+            marks,
+            ..export::Options::default()
+        })
+    }
+}
+
+/// Highlighted code, to be turned into one of the formats.
+#[wasm_bindgen]
+pub struct HighlightResult {
+    stream: TokenStream,
+    theme: &'static Theme,
+    options: export::Options,
+    language: String,
+}
+
+#[wasm_bindgen]
+impl HighlightResult {
+    /// The id of the language the code was highlighted as, see `languages()`.
+    #[wasm_bindgen(getter)]
+    pub fn language(&self) -> String {
+        self.language.clone()
+    }
+
+    /// A `<pre>` with inline styles.
+    #[wasm_bindgen(js_name = toHtml)]
+    pub fn to_html(&self) -> String {
+        html::render(&self.stream, self.theme, &self.options)
+    }
+
+    /// A Rich Text Format document.
+    #[wasm_bindgen(js_name = toRtf)]
+    pub fn to_rtf(&self) -> String {
+        rtf::render(&self.stream, self.theme, &self.options)
+    }
+
+    /// The tokens of every line.
+    pub fn tokens(&self) -> Tokens {
+        parse(&tokens(&self.stream)).unchecked_into()
+    }
+}
+
+/// Highlights `code` as `language`, an alias such as `py`, guessing it when
+/// missing.
+#[wasm_bindgen]
+pub fn highlight(
+    code: &str,
+    language: Option<String>,
+    options: Option<HighlightOptionsObject>,
+) -> Result<HighlightResult, JsError> {
+    let json = options
+        .and_then(|options| js_sys::JSON::stringify(&options).ok())
+        .and_then(|json| json.as_string());
+
+    highlight_with(code, language.as_deref(), json.as_deref()).map_err(|error| JsError::new(&error))
+}
+
+fn highlight_with(
+    code: &str,
+    language: Option<&str>,
+    options: Option<&str>,
+) -> Result<HighlightResult, String> {
+    let brush = match language {
+        Some(language) => {
+            brushes::find(language).ok_or_else(|| format!("unknown language `{language}`"))?
+        }
+        None => detect::language(code)
+            .and_then(brushes::find)
+            .ok_or("the language could not be guessed, pass one")?,
+    };
+    let options = HighlightOptions::parse(options)?;
+
+    let stream = planetb_syntax_highligher::engine::highlight(code, brush);
+
+    Ok(HighlightResult {
+        theme: options.theme()?,
+        options: options.export(stream.lines.len())?,
+        stream,
+        language: ProgrammingLanguage::by_alias(brush.aliases[0])
+            .map_or(brush.aliases[0], |language| language.css_class)
+            .to_string(),
+    })
+}
+
+/// The languages `highlight` knows.
+#[wasm_bindgen]
+pub fn languages() -> Languages {
+    parse(&languages::to_json()).unchecked_into()
+}
+
+fn tokens(stream: &TokenStream) -> serde_json::Value {
+    stream
+        .lines
+        .iter()
+        .map(|line| {
+            line.tokens
+                .iter()
+                .map(|token| {
+                    json!({
+                        "kind": token.kind.css_class().unwrap_or("plain"),
+                        "text": token.text,
+                    })
+                })
+                .collect::<serde_json::Value>()
+        })
+        .collect()
+}
+
+fn parse(value: &serde_json::Value) -> JsValue {
+    js_sys::JSON::parse(&value.to_string()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use planetb_syntax_highligher::engine::export::hostile::{self, Random};
+    use planetb_syntax_highligher::engine::export::ExportFormat;
+    use planetb_syntax_highligher::engine::marks::Emphasis;
+
+    #[test]
+    fn highlights_with_options() {
+        let result = highlight_with(
+            "def f():\n    return 1",
+            Some("py"),
+            Some(r#"{"theme": "grayscale", "lineNumbers": false, "marks": {"highlighted": "2"}}"#),
+        )
+        .unwrap();
+
+        assert_eq!(result.language, "python");
+        assert!(!result.options.line_numbers);
+        assert_eq!(result.options.marks.get(2), Some(Emphasis::Highlighted));
+        assert!(result.to_html().starts_with("<pre"));
+        assert!(result.to_rtf().starts_with("{\\rtf1"));
+
+        let tokens = super::tokens(&result.stream);
+        assert_eq!(tokens[0][0], json!({"kind": "keyword", "text": "def"}));
+    }
+
+    #[test]
+    fn hostile_input_never_becomes_markup() {
+        let mut random = Random::default();
+        let brushes = brushes::all();
+
+        for _ in 0..200 {
+            let code = random.hostile();
+            let brush = &brushes[random.below(brushes.len())];
+            let result = highlight_with(&code, Some(brush.aliases[0]), None).unwrap();
+
+            if let Err(error) = hostile::check(ExportFormat::Html, result.to_html().as_bytes())
+                .and_then(|()| hostile::check(ExportFormat::Rtf, result.to_rtf().as_bytes()))
+            {
+                panic!("{code:?}: {error}");
+            }
+        }
+    }
+
+    #[test]
+    fn reports_invalid_input() {
+        let error = |language, options| highlight_with("x", language, options).err().unwrap();
+
+        assert_eq!(error(Some("cobol"), None), "unknown language `cobol`");
+        assert!(error(Some("sql"), Some(r#"{"linenumbers": true}"#)).starts_with("invalid options"));
+        assert!(
+            error(Some("sql"), Some(r#"{"theme": "neon"}"#)).starts_with("unknown theme `neon`")
+        );
+        assert_eq!(
+            error(
+                Some("sql"),
+                Some(&format!(r#"{{"firstLine": {}}}"#, usize::MAX))
+            ),
+            format!("the first line {} is too large", usize::MAX)
+        );
+    }
+}
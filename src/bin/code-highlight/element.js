@@ -0,0 +1,35 @@
+// Custom elements have to be classes extending `HTMLElement`, which wasm
+// can't declare. This one hands every change over to `render`.
+export function define(name, attributes, render) {
+  customElements.define(
+    name,
+    class extends HTMLElement {
+      static get observedAttributes() {
+        return attributes;
+      }
+
+      connectedCallback() {
+        if (!this.observer) {
+          this.observer = new MutationObserver(() => render(this));
+          this.observer.observe(this, {
+            childList: true,
+            characterData: true,
+            subtree: true,
+          });
+        }
+        render(this);
+      }
+
+      disconnectedCallback() {
+        this.observer?.disconnect();
+        this.observer = null;
+      }
+
+      attributeChangedCallback() {
+        if (this.isConnected) {
+          render(this);
+        }
+      }
+    }
+  );
+}
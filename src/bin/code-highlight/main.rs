@@ -0,0 +1,178 @@
+#![deny(clippy::all)]
+
+//! The `<code-highlight>` custom element, a bundle of its own for pages
+//! without the app:
+//!
+//! ```html
+//! <code-highlight language="python" theme="grayscale" line-numbers start-line="10">
+//! def answer():
+//!     return 42
+//! </code-highlight>
+//! ```
+//!
+//! The text content stays as it is and the highlighted copy goes into the
+//! shadow root, rendered again whenever the text or an attribute changes.
+//! Scripts can use the engine directly, see [`api`].
+
+mod api;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Document, Element, HtmlElement, ShadowRootInit, ShadowRootMode};
+
+use planetb_syntax_highligher::engine::export::html::FONT_FAMILY;
+use planetb_syntax_highligher::engine::theme::{self, Style, Theme};
+use planetb_syntax_highligher::engine::token::{Token, TokenKind, TokenStream};
+use planetb_syntax_highligher::engine::{brushes, detect, highlight};
+
+const NAME: &str = "code-highlight";
+const ATTRIBUTES: [&str; 4] = ["language", "theme", "line-numbers", "start-line"];
+
+#[wasm_bindgen(module = "/src/bin/code-highlight/element.js")]
+extern "C" {
+    fn define(name: &str, attributes: Vec<JsValue>, render: &Closure<dyn Fn(HtmlElement)>);
+}
+
+fn main() {
+    let render = Closure::<dyn Fn(HtmlElement)>::new(render);
+    let attributes = ATTRIBUTES.iter().map(|name| JsValue::from_str(name));
+
+    define(NAME, attributes.collect(), &render);
+    // Lives as long as the element class.
+    render.forget();
+}
+
+/// The settings of `element`, read from its attributes.
+struct Settings {
+    language: Option<String>,
+    theme: &'static Theme,
+    line_numbers: bool,
+    first_line: usize,
+}
+
+impl Settings {
+    fn of(element: &Element) -> Self {
+        let theme = element
+            .get_attribute("theme")
+            .and_then(|name| theme::find(&name).ok())
+            .unwrap_or(&theme::CLASSIC);
+
+        Settings {
+            language: element.get_attribute("language"),
+            theme,
+            // A boolean attribute, `line-numbers="false"` turning it off too.
+            line_numbers: element
+                .get_attribute("line-numbers")
+                .is_some_and(|value| value != "false"),
+            first_line: element
+                .get_attribute("start-line")
+                .and_then(|line| line.trim().parse().ok())
+                .unwrap_or(1),
+        }
+    }
+}
+
+fn render(element: HtmlElement) {
+    let mut settings = Settings::of(&element);
+    let code = element.text_content().unwrap_or_default();
+    // Leading and trailing lines are there to keep the markup readable.
+    let code = code.trim_matches(['\n', '\r']);
+
+    let brush = settings
+        .language
+        .as_deref()
+        .or_else(|| detect::language(code))
+        .and_then(brushes::find);
+    let stream = match brush {
+        Some(brush) => highlight(code, brush),
+        None => TokenStream::from_tokens(vec![Token::new(TokenKind::Plain, code)]),
+    };
+    // The numbers of the last lines wouldn't fit into a `usize`.
+    if settings
+        .first_line
+        .checked_add(stream.lines.len())
+        .is_none()
+    {
+        settings.first_line = 1;
+    }
+
+    let root = match element.shadow_root() {
+        Some(root) => root,
+        None => match element.attach_shadow(&ShadowRootInit::new(ShadowRootMode::Open)) {
+            Ok(root) => root,
+            Err(_) => return,
+        },
+    };
+    let document = element.owner_document().unwrap();
+    root.set_text_content(None);
+    let _ = root.append_child(&pre(&document, &stream, &settings));
+}
+
+/// Styles `element` through its style object, which a Content-Security-Policy
+/// without `unsafe-inline` still allows.
+fn styled(document: &Document, tag: &str, properties: &[(&str, &str)]) -> HtmlElement {
+    let element = document
+        .create_element(tag)
+        .unwrap()
+        .unchecked_into::<HtmlElement>();
+    let style = element.style();
+    for (name, value) in properties {
+        let _ = style.set_property(name, value);
+    }
+    element
+}
+
+fn span(document: &Document, style: &Style, text: &str) -> HtmlElement {
+    let color = style.color.to_hex();
+    let mut properties = vec![("color", color.as_str())];
+    if style.bold {
+        properties.push(("font-weight", "bold"));
+    }
+    if style.italic {
+        properties.push(("font-style", "italic"));
+    }
+
+    let span = styled(document, "span", &properties);
+    span.set_text_content(Some(text));
+    span
+}
+
+fn pre(document: &Document, stream: &TokenStream, settings: &Settings) -> HtmlElement {
+    let theme = settings.theme;
+    let background = theme.background.to_hex();
+    let pre = styled(
+        document,
+        "pre",
+        &[
+            ("font-family", FONT_FAMILY),
+            ("font-size", "10pt"),
+            ("margin", "0"),
+            ("padding", "8px"),
+            ("overflow-x", "auto"),
+            ("background-color", &background),
+        ],
+    );
+
+    let last = settings.first_line + stream.lines.len().max(1) - 1;
+    let gutter_width = last.to_string().len();
+    let line_number = Style {
+        color: theme.line_number,
+        ..theme.text
+    };
+
+    for (index, line) in stream.lines.iter().enumerate() {
+        if index > 0 {
+            let _ = pre.append_with_str_1("\n");
+        }
+        if settings.line_numbers {
+            let number = format!("{:>gutter_width$} ", settings.first_line + index);
+            let _ = pre.append_child(&span(document, &line_number, &number));
+        }
+        for token in &line.tokens {
+            let style = theme.style(token.kind);
+            let _ = pre.append_child(&span(document, &style, &token.text));
+        }
+    }
+
+    pre
+}
@@ -0,0 +1,256 @@
+//! The REST API: `POST /highlight` renders code in one of the export formats
+//! and `GET /languages` lists the languages it can highlight.
+
+use std::collections::BTreeMap;
+
+use serde_derive::Deserialize;
+
+use planetb_syntax_highligher::engine::annotations::{Annotation, Annotations};
+use planetb_syntax_highligher::engine::export::{self, ansi, docx, scene, ExportFormat};
+use planetb_syntax_highligher::engine::marks::LineMarks;
+use planetb_syntax_highligher::engine::{brushes, detect, languages, theme};
+
+use crate::http::{Request, Response};
+
+/// The same limit the app has for opened files.
+pub const MAX_BODY: usize = 5 * 1024 * 1024;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HighlightRequest {
+    code: String,
+    /// An alias of a brush, e.g. `python` or `py`, guessed if missing.
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    theme: Option<String>,
+    /// The extension of an export format, `html` if missing.
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    options: HighlightOptions,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct HighlightOptions {
+    line_numbers: bool,
+    first_line: usize,
+    /// Line ranges such as `3,7-9` by emphasis, e.g. `highlighted`.
+    marks: BTreeMap<String, String>,
+    annotations: Vec<Annotation>,
+    /// Palette of ANSI text: `16`, `256` or `truecolor`.
+    colors: Option<String>,
+    /// Draws SVG images in a window with a title bar.
+    chrome: bool,
+    /// Casts a shadow below SVG images.
+    shadow: bool,
+    /// Draws Word documents in a shaded box.
+    boxed: bool,
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        HighlightOptions {
+            line_numbers: true,
+            first_line: 1,
+            marks: BTreeMap::new(),
+            annotations: Vec::new(),
+            colors: None,
+            chrome: true,
+            shadow: false,
+            boxed: true,
+        }
+    }
+}
+
+/// Answers `request`, errors being JSON objects with an `error` message.
+pub fn handle(request: &Request) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/languages") => languages(),
+        ("POST", "/highlight") => {
+            highlight(&request.body).unwrap_or_else(|message| Response::error(400, &message))
+        }
+        (_, "/languages") => {
+            Response::error(405, "use GET for /languages").with_header("Allow", "GET")
+        }
+        (_, "/highlight") => {
+            Response::error(405, "use POST for /highlight").with_header("Allow", "POST")
+        }
+        _ => Response::error(404, "not found"),
+    }
+}
+
+fn languages() -> Response {
+    Response::json(200, &languages::to_json())
+}
+
+fn highlight(body: &[u8]) -> Result<Response, String> {
+    let request = serde_json::from_slice::<HighlightRequest>(body)
+        .map_err(|error| format!("invalid request: {error}"))?;
+
+    let brush = match &request.language {
+        Some(language) => {
+            brushes::find(language).ok_or_else(|| format!("unknown language `{language}`"))?
+        }
+        None => detect::language(&request.code)
+            .and_then(brushes::find)
+            .ok_or("the language could not be guessed, set `language`")?,
+    };
+    let theme = theme::find(request.theme.as_deref().unwrap_or("classic"))?;
+    let format = match &request.format {
+        Some(format) => format.parse::<ExportFormat>()?,
+        None => ExportFormat::Html,
+    };
+
+    let options = request.options;
+    let lines = request.code.split('\n').count();
+    if options.first_line.checked_add(lines).is_none() {
+        return Err(format!(
+            "the first line {} is too large",
+            options.first_line
+        ));
+    }
+    let marks = LineMarks::from_specs(&options.marks, lines)?;
+    let mut annotations = Annotations::default();
+    for annotation in &options.annotations {
+        annotations.add(annotation.line, &annotation.note);
+    }
+    let settings = export::Settings {
+        docx: docx::Layout {
+            boxed: options.boxed,
+        },
+        image: scene::Layout {
+            chrome: options.chrome,
+            shadow: options.shadow,
+            ..scene::Layout::default()
+        },
+        colors: match &options.colors {
+            Some(colors) => colors.parse::<ansi::ColorMode>()?,
+            None => ansi::ColorMode::default(),
+        },
+    };
+
+    let stream = planetb_syntax_highligher::engine::highlight(&request.code, brush);
+    let file = export::File {
+        name: "code",
+        stream: &stream,
+        options: export::Options {
+            line_numbers: options.line_numbers,
+            first_line: options.first_line,
+            marks,
+            annotations,
+        },
+    };
+    let rendered =
+        export::render(format, &[file], theme, &settings).ok_or("there is nothing to render")?;
+
+    let content_type = match format.mime_type() {
+        text if text.starts_with("text/") => format!("{text}; charset=utf-8"),
+        binary => binary.to_string(),
+    };
+    Ok(Response::new(200, &content_type, rendered))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn request(method: &str, path: &str, body: &str) -> Response {
+        handle(&Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: Vec::new(),
+            body: body.as_bytes().to_vec(),
+        })
+    }
+
+    fn body(response: &Response) -> String {
+        String::from_utf8(response.body.clone()).unwrap()
+    }
+
+    #[test]
+    fn highlights_in_the_requested_format() {
+        let response = request(
+            "POST",
+            "/highlight",
+            r#"{"code": "def f():\n    return 1", "language": "python", "format": "rtf"}"#,
+        );
+
+        assert_eq!(response.status, 200);
+        assert!(response
+            .headers
+            .contains(&("Content-Type".to_string(), "application/rtf".to_string())));
+        assert!(body(&response).starts_with("{\\rtf1"));
+    }
+
+    #[test]
+    fn guesses_the_language_and_defaults_to_html() {
+        let response = request(
+            "POST",
+            "/highlight",
+            r#"{"code": "<?php echo 1; ?>", "options": {"marks": {"highlighted": "1"}}}"#,
+        );
+
+        assert_eq!(response.status, 200);
+        assert!(body(&response).starts_with("<pre"));
+    }
+
+    #[test]
+    fn reports_invalid_requests() {
+        let unknown = request(
+            "POST",
+            "/highlight",
+            r#"{"code": "x", "language": "cobol"}"#,
+        );
+        assert_eq!(unknown.status, 400);
+        assert!(body(&unknown).contains("unknown language `cobol`"));
+
+        let ranges = request(
+            "POST",
+            "/highlight",
+            r#"{"code": "x", "language": "sql", "options": {"marks": {"dimmed": "3-1"}}}"#,
+        );
+        assert_eq!(ranges.status, 400);
+        assert!(body(&ranges).contains("invalid dimmed lines"));
+
+        let huge = request(
+            "POST",
+            "/highlight",
+            &format!(
+                r#"{{"code": "x\ny", "language": "sql", "options": {{"marks": {{"highlighted": "1-{}"}}}}}}"#,
+                usize::MAX
+            ),
+        );
+        assert_eq!(huge.status, 400);
+        assert!(body(&huge).contains("past the last line, 2"));
+
+        let first_line = request(
+            "POST",
+            "/highlight",
+            &format!(
+                r#"{{"code": "x", "language": "sql", "options": {{"first_line": {}}}}}"#,
+                usize::MAX
+            ),
+        );
+        assert_eq!(first_line.status, 400);
+
+        assert_eq!(request("GET", "/highlight", "").status, 405);
+        assert_eq!(request("GET", "/missing", "").status, 404);
+    }
+
+    #[test]
+    fn lists_the_languages() {
+        let response = request("GET", "/languages", "");
+        let languages: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+
+        assert_eq!(
+            languages.as_array().unwrap().len(),
+            languages::PROGRAMMING_LANGUAGES.len()
+        );
+        assert_eq!(languages[2]["id"], "python");
+        assert_eq!(languages[2]["aliases"], json!(["py", "python"]));
+    }
+}
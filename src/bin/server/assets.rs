@@ -0,0 +1,262 @@
+//! The web app built by Trunk, embedded by `build.rs` and served with the
+//! encoding the client prefers and caching suited to its file names.
+
+use crate::http::{Request, Response};
+
+pub struct Asset {
+    /// Path below the root, e.g. `/index.html`.
+    pub path: &'static str,
+    pub content: &'static [u8],
+    pub gzip: Option<&'static [u8]>,
+    pub brotli: Option<&'static [u8]>,
+    /// Hash of the content, which the ETags of every encoding start with.
+    pub etag: &'static str,
+}
+
+pub static ASSETS: &[Asset] = include!(concat!(env!("OUT_DIR"), "/assets.rs"));
+/// CSP hash sources of the inline scripts of the pages, e.g. `'sha256-…'`.
+pub static INLINE_SCRIPTS: &[&str] = include!(concat!(env!("OUT_DIR"), "/inline_scripts.rs"));
+
+/// Assets whose name holds a content hash never change under that name.
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// Everything else is revalidated with its ETag before every use.
+const REVALIDATE: &str = "no-cache";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Encoding {
+    Brotli,
+    Gzip,
+    Identity,
+}
+
+/// Answers `GET` and `HEAD` requests for embedded files, `/` being the app.
+pub fn handle(request: &Request) -> Response {
+    if request.method != "GET" && request.method != "HEAD" {
+        return Response::error(405, "only GET and HEAD are allowed")
+            .with_header("Allow", "GET, HEAD");
+    }
+
+    let path = match request.path.as_str() {
+        "/" => "/index.html",
+        path => path,
+    };
+    let Some(asset) = ASSETS.iter().find(|asset| asset.path == path) else {
+        return Response::error(404, "not found");
+    };
+
+    let cache_control = if is_hashed(asset.path) {
+        IMMUTABLE
+    } else {
+        REVALIDATE
+    };
+    let accepted = request.header("Accept-Encoding").unwrap_or_default();
+    let (encoding, body) = match negotiate(accepted, asset) {
+        Encoding::Brotli => ("br", asset.brotli),
+        Encoding::Gzip => ("gzip", asset.gzip),
+        Encoding::Identity => ("", None),
+    };
+    // Every encoding is a representation of its own.
+    let etag = match body {
+        Some(_) => format!("\"{}-{encoding}\"", asset.etag),
+        None => format!("\"{}\"", asset.etag),
+    };
+
+    let fresh = request.header("If-None-Match").is_some_and(|tags| {
+        tags.split(',')
+            .any(|tag| tag.trim() == etag || tag.trim() == "*")
+    });
+    if fresh {
+        return Response {
+            status: 304,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+        .with_header("ETag", &etag)
+        .with_header("Cache-Control", cache_control);
+    }
+
+    let mut response = Response::new(200, content_type(asset.path), body.unwrap_or(asset.content))
+        .with_header("ETag", &etag)
+        .with_header("Cache-Control", cache_control);
+    if body.is_some() {
+        response = response.with_header("Content-Encoding", encoding);
+    }
+    if asset.gzip.is_some() || asset.brotli.is_some() {
+        response = response.with_header("Vary", "Accept-Encoding");
+    }
+    response
+}
+
+/// The quality the client gives `coding` in `Accept-Encoding`, 0 if none.
+fn quality(accepted: &str, coding: &str) -> f32 {
+    accepted
+        .split(',')
+        .filter_map(|part| {
+            let mut parameters = part.split(';').map(str::trim);
+            let name = parameters.next()?;
+            if !name.eq_ignore_ascii_case(coding) && name != "*" {
+                return None;
+            }
+
+            let quality = parameters
+                .find_map(|parameter| parameter.strip_prefix("q="))
+                .map_or(Some(1.0), |quality| quality.parse().ok())?;
+            // An exact match outranks the wildcard.
+            Some((name != "*", quality))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map_or(0.0, |(_, quality)| quality)
+}
+
+fn negotiate(accepted: &str, asset: &Asset) -> Encoding {
+    let brotli = if asset.brotli.is_some() {
+        quality(accepted, "br")
+    } else {
+        0.0
+    };
+    let gzip = if asset.gzip.is_some() {
+        quality(accepted, "gzip")
+    } else {
+        0.0
+    };
+
+    if brotli > 0.0 && brotli >= gzip {
+        Encoding::Brotli
+    } else if gzip > 0.0 {
+        Encoding::Gzip
+    } else {
+        Encoding::Identity
+    }
+}
+
+/// Whether Trunk put a content hash into the name, e.g. `app-1a2b…_bg.wasm`.
+fn is_hashed(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+
+    name.split(['-', '_', '.'])
+        .any(|part| part.len() == 16 && part.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn content_type(path: &str) -> &'static str {
+    let extension = path.rsplit_once('.').map_or("", |(_, extension)| extension);
+
+    match extension {
+        "html" => "text/html; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "wasm" => "application/wasm",
+        "css" => "text/css; charset=utf-8",
+        "json" => "application/json",
+        "webmanifest" => "application/manifest+json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff2" => "font/woff2",
+        "woff" => "font/woff",
+        "ttf" => "font/ttf",
+        "txt" => "text/plain; charset=utf-8",
+        "xml" => "application/xml",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASSET: Asset = Asset {
+        path: "/app.js",
+        content: b"content",
+        gzip: Some(b"gzip"),
+        brotli: Some(b"br"),
+        etag: "1",
+    };
+
+    #[test]
+    fn negotiates_the_preferred_encoding() {
+        assert_eq!(negotiate("gzip, deflate, br", &ASSET), Encoding::Brotli);
+        assert_eq!(negotiate("br;q=0.5, gzip", &ASSET), Encoding::Gzip);
+        assert_eq!(negotiate("br;q=0, *;q=0.1", &ASSET), Encoding::Gzip);
+        assert_eq!(negotiate("identity", &ASSET), Encoding::Identity);
+        assert_eq!(negotiate("", &ASSET), Encoding::Identity);
+
+        let plain = Asset {
+            brotli: None,
+            ..ASSET
+        };
+        assert_eq!(negotiate("br", &plain), Encoding::Identity);
+    }
+
+    #[test]
+    fn only_hashed_names_are_immutable() {
+        assert!(is_hashed(
+            "/planetb-syntax-highligher-9c2f0e7b3a1d4e5f_bg.wasm"
+        ));
+        assert!(is_hashed("/SyntaxHighlighter-0123456789abcdef.css"));
+        assert!(!is_hashed("/index.html"));
+        assert!(!is_hashed("/images/background.svg"));
+    }
+
+    /// URLs of other origins in `html`, including protocol relative ones.
+    fn external_urls(html: &str) -> Vec<&str> {
+        ["http://", "https://", "=\"//", "='//", "=//"]
+            .iter()
+            .flat_map(|scheme| html.match_indices(scheme))
+            .map(|(start, scheme)| {
+                // The URL of `="//…` starts after the `="`.
+                let url = &html
+                    [start + scheme.len() - scheme.trim_start_matches(['=', '"', '\'']).len()..];
+                &url[..url.find(['"', '\'', ' ', '>']).unwrap_or(url.len())]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_urls_of_other_origins() {
+        assert_eq!(
+            external_urls(
+                "<script src=\"https://kit.example/a.js\"></script><img src=//cdn>\
+                 <link href=\"//fonts.example/css\"><a href='//b.example'>"
+            ),
+            [
+                "https://kit.example/a.js",
+                "//fonts.example/css",
+                "//b.example",
+                "//cdn"
+            ]
+        );
+        assert_eq!(external_urls("<a href=\"/local\">"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn pages_load_nothing_from_other_origins() {
+        let source = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/index.html"));
+        assert_eq!(external_urls(source), Vec::<&str>::new());
+
+        // Only builds with `SERVER_WITHOUT_APP` embed nothing, see `build.rs`.
+        if ASSETS.is_empty() {
+            eprintln!("no app is embedded, skipping the pages of dist/");
+            return;
+        }
+        let pages = ASSETS
+            .iter()
+            .filter(|asset| asset.path.ends_with(".html"))
+            .collect::<Vec<_>>();
+        assert!(pages.iter().any(|asset| asset.path == "/index.html"));
+        for asset in pages {
+            let html = String::from_utf8_lossy(asset.content);
+            assert_eq!(external_urls(&html), Vec::<&str>::new(), "{}", asset.path);
+        }
+    }
+
+    #[test]
+    fn types_follow_the_extension() {
+        assert_eq!(content_type("/app_bg.wasm"), "application/wasm");
+        assert_eq!(
+            content_type("/manifest.webmanifest"),
+            "application/manifest+json"
+        );
+        assert_eq!(content_type("/LICENSE"), "application/octet-stream");
+    }
+}
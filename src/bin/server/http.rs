@@ -0,0 +1,257 @@
+//! Just enough of HTTP/1.1 to answer one request per connection.
+
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+
+/// Longer request lines and headers are not sent by any client we serve.
+const MAX_LINE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+
+pub struct Request {
+    pub method: String,
+    /// The path without the query string.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// The value of the header `name`, which is case insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The request isn't HTTP, e.g. a missing method or header colon.
+    Malformed(&'static str),
+    /// The body is larger than allowed.
+    TooLarge,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{error}"),
+            Error::Malformed(reason) => write!(f, "malformed request: {reason}"),
+            Error::TooLarge => write!(f, "the request body is too large"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, Error> {
+    let mut line = Vec::new();
+    Read::take(&mut *reader, MAX_LINE as u64 + 1).read_until(b'\n', &mut line)?;
+
+    if line.len() > MAX_LINE {
+        return Err(Error::Malformed("line too long"));
+    }
+    if !line.ends_with(b"\n") {
+        return Err(Error::Malformed("unexpected end of request"));
+    }
+
+    let line = String::from_utf8(line).map_err(|_| Error::Malformed("line is not UTF-8"))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Reads a request with a body of at most `max_body` bytes.
+pub fn read_request(reader: &mut impl BufRead, max_body: usize) -> Result<Request, Error> {
+    let request_line = read_line(reader)?;
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Error::Malformed("invalid request line"));
+    };
+    if !version.starts_with("HTTP/1.") || method.is_empty() {
+        return Err(Error::Malformed("invalid request line"));
+    }
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(Error::Malformed("too many headers"));
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or(Error::Malformed("header without a colon"))?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let mut request = Request {
+        method: method.to_string(),
+        path: target.split('?').next().unwrap_or_default().to_string(),
+        headers,
+        body: Vec::new(),
+    };
+
+    if request.header("Transfer-Encoding").is_some() {
+        return Err(Error::Malformed("chunked bodies are not supported"));
+    }
+    let length = match request.header("Content-Length") {
+        Some(length) => length
+            .parse::<usize>()
+            .map_err(|_| Error::Malformed("invalid Content-Length"))?,
+        None => 0,
+    };
+    if length > max_body {
+        return Err(Error::TooLarge);
+    }
+
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body)?;
+
+    Ok(request)
+}
+
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Response {
+            status,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body: body.into(),
+        }
+    }
+
+    pub fn json(status: u16, value: &serde_json::Value) -> Self {
+        Response::new(status, "application/json", value.to_string())
+    }
+
+    /// A JSON object with the `error` message.
+    pub fn error(status: u16, message: &str) -> Self {
+        Response::json(status, &serde_json::json!({ "error": message }))
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Writes the response and announces that the connection is closed.
+    /// Answers to `HEAD` requests leave out the body, but not its length.
+    pub fn write_to(&self, writer: &mut impl Write, with_body: bool) -> io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\n",
+            self.status,
+            reason(self.status)
+        )?;
+        for (name, value) in &self.headers {
+            write!(writer, "{name}: {value}\r\n")?;
+        }
+        if self.status != 304 {
+            write!(writer, "Content-Length: {}\r\n", self.body.len())?;
+        }
+        write!(writer, "Connection: close\r\n\r\n")?;
+        if with_body {
+            writer.write_all(&self.body)?;
+        }
+        writer.flush()
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_a_request_with_a_body() {
+        let raw =
+            b"POST /highlight?x=1 HTTP/1.1\r\nHost: localhost\r\ncontent-length: 4\r\n\r\nbodyrest";
+        let request = read_request(&mut &raw[..], 1024).unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/highlight");
+        assert_eq!(request.header("Host"), Some("localhost"));
+        assert_eq!(request.body, b"body");
+    }
+
+    #[test]
+    fn rejects_large_and_malformed_requests() {
+        let large = b"POST / HTTP/1.1\r\nContent-Length: 2048\r\n\r\n";
+        assert!(matches!(
+            read_request(&mut &large[..], 1024),
+            Err(Error::TooLarge)
+        ));
+
+        let malformed = b"GET /\r\n\r\n";
+        assert!(matches!(
+            read_request(&mut &malformed[..], 1024),
+            Err(Error::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn writes_the_length_of_the_body() {
+        let mut written = Vec::new();
+        Response::new(200, "text/plain", "hello")
+            .write_to(&mut written, true)
+            .unwrap();
+        let written = String::from_utf8(written).unwrap();
+
+        assert!(written.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(written.contains("Content-Length: 5\r\n"));
+        assert!(written.ends_with("\r\n\r\nhello"));
+
+        let mut head = Vec::new();
+        Response::new(200, "text/plain", "hello")
+            .write_to(&mut head, false)
+            .unwrap();
+        let head = String::from_utf8(head).unwrap();
+
+        assert!(head.contains("Content-Length: 5\r\n"));
+        assert!(head.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn names_the_status() {
+        let status_line = |status| {
+            let mut written = Vec::new();
+            Response::error(status, "message")
+                .write_to(&mut written, false)
+                .unwrap();
+            let written = String::from_utf8(written).unwrap();
+            written.lines().next().unwrap().to_string()
+        };
+
+        assert_eq!(status_line(404), "HTTP/1.1 404 Not Found");
+        assert_eq!(status_line(413), "HTTP/1.1 413 Payload Too Large");
+        assert_eq!(status_line(500), "HTTP/1.1 500 Internal Server Error");
+        assert_eq!(status_line(503), "HTTP/1.1 503 Service Unavailable");
+    }
+}
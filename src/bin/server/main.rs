@@ -0,0 +1,192 @@
+#![deny(clippy::all)]
+
+//! Serves the web app, embedded from `dist/` by `build.rs`, and highlights
+//! code over HTTP for pipelines without a browser, see [`api`]. Listens on
+//! the address given as the only argument.
+
+mod api;
+mod assets;
+mod http;
+
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use http::Response;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+/// Slow clients give up their thread after this long.
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// Threads serving connections, however many clients there are.
+const WORKERS: usize = 16;
+/// Connections waiting for a worker, more are turned away with a 503.
+const QUEUE: usize = 64;
+/// Sent with every response. TLS and with it `Strict-Transport-Security` are
+/// left to the proxy in front.
+const SECURITY_HEADERS: [(&str, &str); 5] = [
+    ("X-Content-Type-Options", "nosniff"),
+    ("Referrer-Policy", "no-referrer"),
+    ("Cross-Origin-Opener-Policy", "same-origin"),
+    ("Cross-Origin-Resource-Policy", "same-origin"),
+    (
+        "Permissions-Policy",
+        "camera=(), geolocation=(), microphone=(), payment=(), usb=()",
+    ),
+];
+/// Pages which may embed the app in an iframe, separated by commas, the same
+/// the app itself answers to when built with them.
+const EMBED_ORIGINS: &str = match option_env!("EMBED_ORIGINS") {
+    Some(origins) => origins,
+    None => "",
+};
+/// Nothing but the app's own files may run or load. The module Trunk starts
+/// the app with is the only inline script and allowed by its hash. Styles
+/// come from the style sheets and the CSSOM, never from attributes.
+static CONTENT_SECURITY_POLICY: LazyLock<String> = LazyLock::new(|| {
+    let scripts = assets::INLINE_SCRIPTS
+        .iter()
+        .map(|source| format!(" {source}"))
+        .collect::<String>();
+
+    format!(
+        "default-src 'self'; script-src 'self' 'wasm-unsafe-eval'{scripts}; style-src 'self'; img-src 'self' data: blob:; font-src 'self'; connect-src 'self'; object-src 'none'; base-uri 'none'; form-action 'none'; frame-ancestors {}",
+        frame_ancestors(EMBED_ORIGINS)
+    )
+});
+
+/// The CSP source list of the pages allowed to embed the app.
+fn frame_ancestors(origins: &str) -> String {
+    let origins = origins
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .collect::<Vec<_>>();
+
+    match origins.as_slice() {
+        [] => "'none'".to_string(),
+        origins => origins.join(" "),
+    }
+}
+
+fn main() {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    if address == "-h" || address == "--help" {
+        println!("Usage: server [ADDRESS], ADDRESS defaulting to {DEFAULT_ADDRESS}");
+        return;
+    }
+
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Listening on {address} failed: {error}");
+            std::process::exit(1);
+        }
+    };
+    if assets::ASSETS.is_empty() {
+        eprintln!("No web app embedded, build it with Trunk before the server to serve it");
+    }
+    println!("Listening on http://{address}");
+
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(QUEUE);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..WORKERS {
+        let receiver = Arc::clone(&receiver);
+        thread::spawn(move || work(&receiver));
+    }
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => match sender.try_send(stream) {
+                Ok(()) => {}
+                Err(TrySendError::Full(stream)) => turn_away(&stream),
+                Err(TrySendError::Disconnected(_)) => unreachable!("the workers never stop"),
+            },
+            Err(error) => eprintln!("Accepting a connection failed: {error}"),
+        }
+    }
+}
+
+/// Serves the connections of `receiver` one after the other. Release builds
+/// abort on a panic, so requests are checked before anything is rendered.
+fn work(receiver: &Mutex<Receiver<TcpStream>>) {
+    loop {
+        // The lock is released before serving, for the other workers to wait.
+        let stream = receiver.lock().unwrap().recv();
+        match stream {
+            Ok(stream) => serve(stream),
+            Err(_) => return,
+        }
+    }
+}
+
+/// Answers without reading the request, with every worker busy.
+fn turn_away(stream: &TcpStream) {
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+    let response = Response::error(503, "too many connections, try again later")
+        .with_header("Retry-After", "1");
+    let _ = response.write_to(&mut &*stream, true);
+}
+
+fn serve(stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(TIMEOUT));
+    let _ = stream.set_write_timeout(Some(TIMEOUT));
+
+    let (mut response, with_body) =
+        match http::read_request(&mut BufReader::new(&stream), api::MAX_BODY) {
+            Ok(request) => (route(&request), request.method != "HEAD"),
+            Err(error @ http::Error::TooLarge) => (Response::error(413, &error.to_string()), true),
+            Err(error @ http::Error::Malformed(_)) => {
+                (Response::error(400, &error.to_string()), true)
+            }
+            Err(http::Error::Io(_)) => return,
+        };
+
+    response = response.with_header("Content-Security-Policy", &CONTENT_SECURITY_POLICY);
+    // Superseded by `frame-ancestors`, but the only protection of old browsers.
+    if frame_ancestors(EMBED_ORIGINS) == "'none'" {
+        response = response.with_header("X-Frame-Options", "DENY");
+    }
+    for (name, value) in SECURITY_HEADERS {
+        response = response.with_header(name, value);
+    }
+    if let Err(error) = response.write_to(&mut &stream, with_body) {
+        eprintln!("Writing a response failed: {error}");
+    }
+}
+
+fn route(request: &http::Request) -> Response {
+    match request.path.as_str() {
+        "/highlight" | "/languages" => api::handle(request),
+        _ => assets::handle(request),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_inline_but_the_hashed_scripts_is_allowed() {
+        assert!(!CONTENT_SECURITY_POLICY.contains("unsafe-inline"));
+        assert!(CONTENT_SECURITY_POLICY.contains("style-src 'self';"));
+        assert!(!CONTENT_SECURITY_POLICY.contains("style-src-attr"));
+    }
+
+    #[test]
+    fn only_listed_pages_may_embed_the_app() {
+        assert_eq!(frame_ancestors(""), "'none'");
+        assert_eq!(
+            frame_ancestors("https://wiki.example.com, https://docs.example.com"),
+            "https://wiki.example.com https://docs.example.com"
+        );
+        assert!(CONTENT_SECURITY_POLICY.ends_with(&format!(
+            "frame-ancestors {}",
+            frame_ancestors(EMBED_ORIGINS)
+        )));
+    }
+}
@@ -0,0 +1,79 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+
+use crate::engine::diff::{Change, Diff, Segment};
+
+#[derive(Properties)]
+pub struct Props {
+    pub diff: Rc<Diff>,
+}
+
+impl PartialEq for Props {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.diff, &other.diff)
+    }
+}
+
+/// Both versions of a snippet next to each other, with removed, added and
+/// changed lines marked and the changed words within them emphasized.
+pub struct DiffPreview;
+
+impl DiffPreview {
+    fn view_side(line: Option<usize>, segments: &[Segment], side: &'static str) -> Html {
+        html! {
+            <>
+                <td class="gutter">{line.map(|line| (line + 1).to_string()).unwrap_or_default()}</td>
+                <td class={classes!("code", side)}>
+                    {
+                        segments.iter().map(|segment| {
+                            html! {
+                                <span class={classes!(segment.kind.css_class(), segment.marked.then_some("marked"))}>
+                                    {segment.text}
+                                </span>
+                            }
+                        }).collect::<Html>()
+                    }
+                </td>
+            </>
+        }
+    }
+}
+
+impl Component for DiffPreview {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        DiffPreview
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let diff = &ctx.props().diff;
+
+        html! {
+            <div class="dp-highlighter diff">
+                <table>
+                    {
+                        diff.rows.iter().map(|row| {
+                            let (before, after) = diff.segments(row);
+                            let change = match row.change {
+                                Change::Equal => None,
+                                Change::Removed => Some("removed"),
+                                Change::Added => Some("added"),
+                                Change::Changed => Some("changed"),
+                            };
+
+                            html! {
+                                <tr class={classes!(change)}>
+                                    {Self::view_side(row.before, &before, "before")}
+                                    {Self::view_side(row.after, &after, "after")}
+                                </tr>
+                            }
+                        }).collect::<Html>()
+                    }
+                </table>
+            </div>
+        }
+    }
+}
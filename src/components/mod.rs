@@ -0,0 +1,4 @@
+pub mod diff_preview;
+pub mod print_pages;
+pub mod snippet_library;
+pub mod virtual_preview;
@@ -0,0 +1,287 @@
+use web_sys::CssStyleSheet;
+use yew::prelude::*;
+
+use crate::engine::annotations;
+use crate::engine::export::print::{self, Layout, Part};
+use crate::engine::export::{html, File, Options};
+use crate::engine::marks::Emphasis;
+use crate::engine::theme::Theme;
+use crate::engine::token::Line;
+
+/// The pages of a print, styled like the HTML export but built from elements
+/// and text nodes, so no code ends up as markup. The colors of the theme come
+/// from the rules [`style`] adds.
+pub fn view(files: &[File], layout: &Layout) -> Html {
+    let pages = print::pages(files, layout);
+    let count = pages.len();
+
+    pages
+        .iter()
+        .enumerate()
+        .map(|(number, parts)| {
+            let names = parts
+                .iter()
+                .map(|part| files[part.file].name)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            html! {
+                <section class="print-page">
+                    if layout.headers {
+                        <header>
+                            <span>{names}</span>
+                            <span>{format!("Page {} of {count}", number + 1)}</span>
+                        </header>
+                    }
+                    {
+                        parts.iter().map(|part| {
+                            view_part(&files[part.file], part, files.len() > 1)
+                        }).collect::<Html>()
+                    }
+                </section>
+            }
+        })
+        .collect()
+}
+
+/// Styles the pages in the colors of `theme`. The rules go into a style sheet
+/// of their own through the CSSOM, which the Content-Security-Policy allows
+/// unlike style attributes.
+pub fn style(theme: &Theme) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+        return;
+    };
+    let Ok(sheet) = CssStyleSheet::new() else {
+        return;
+    };
+    // Each rule goes in first, so they end up in their order.
+    for rule in rules(theme).iter().rev() {
+        if let Err(error) = sheet.insert_rule(rule) {
+            log::debug!("Adding `{rule}` failed: {:?}", error);
+        }
+    }
+    document.set_adopted_style_sheets(&js_sys::Array::of1(&sheet));
+}
+
+/// The rules of the colors of `theme`, the layout is in `SyntaxHighlighter.css`.
+fn rules(theme: &Theme) -> Vec<String> {
+    let mut rules = vec![
+        format!(
+            ".print-page {{ font-family: {}; color: {}; }}",
+            html::FONT_FAMILY,
+            theme.text.color.to_hex()
+        ),
+        format!(
+            ".print-page header, .print-page .line-number {{ color: {0}; border-color: {0}; }}",
+            theme.line_number.to_hex()
+        ),
+        format!(
+            ".print-page pre {{ background-color: {}; }}",
+            theme.background.to_hex()
+        ),
+        format!(
+            ".print-page .dimmed {{ color: {}; }}",
+            theme.dim(theme.text).color.to_hex()
+        ),
+        format!(".print-page .callout {{ {} }}", html::css(&theme.callout)),
+    ];
+
+    for emphasis in Emphasis::ALL {
+        if let Some(background) = theme.emphasis_background(emphasis) {
+            rules.push(format!(
+                ".print-page .{} {{ background-color: {}; }}",
+                emphasis.css_class(),
+                background.to_hex()
+            ));
+        }
+    }
+    for (kind, style) in theme.styles {
+        if let Some(class) = kind.css_class() {
+            rules.push(format!(".print-page .{class} {{ {} }}", html::css(style)));
+            rules.push(format!(
+                ".print-page .dimmed .{class} {{ {} }}",
+                html::css(&theme.dim(*style))
+            ));
+        }
+    }
+
+    rules
+}
+
+fn view_part(file: &File, part: &Part, named: bool) -> Html {
+    let lines = &file.stream.lines;
+    let gutter_width = file.options.gutter_width(lines.len());
+
+    html! {
+        <>
+            if named && part.start == 0 {
+                <div class="file-name">{file.name}</div>
+            }
+            <pre>
+                {
+                    (part.start..part.end).map(|index| html! {
+                        <>
+                            if index > part.start {
+                                {"\n"}
+                            }
+                            {view_line(&lines[index], index, &file.options, gutter_width)}
+                        </>
+                    }).collect::<Html>()
+                }
+            </pre>
+            if part.end == lines.len() {
+                {view_legend(&file.options)}
+            }
+        </>
+    }
+}
+
+/// The elements [`html::line`] writes out.
+fn view_line(line: &Line, index: usize, options: &Options, gutter_width: usize) -> Html {
+    let emphasis = options.marks.get(index + 1);
+    let callouts = options.annotations.numbers_on(index + 1);
+    let content = html! {
+        <>
+            if options.line_numbers {
+                <span class="line-number">
+                    {format!("{:>gutter_width$} ", options.first_line + index)}
+                </span>
+            }
+            {
+                line.tokens.iter().map(|token| match token.kind.css_class() {
+                    Some(class) => html! { <span class={class}>{token.text.as_str()}</span> },
+                    None => html! { {token.text.as_str()} },
+                }).collect::<Html>()
+            }
+            if !callouts.is_empty() {
+                {" "}
+                <span class="callout">{annotations::markers(&callouts)}</span>
+            }
+        </>
+    };
+
+    match emphasis {
+        Some(emphasis) => html! {
+            <span class={classes!("emphasized", emphasis.css_class())}>{content}</span>
+        },
+        None => content,
+    }
+}
+
+/// The elements [`html::legend`] writes out.
+fn view_legend(options: &Options) -> Html {
+    if options.annotations.is_empty() {
+        return Html::default();
+    }
+
+    html! {
+        <div class="legend">
+            {
+                options.annotations.numbered().map(|(number, annotation)| html! {
+                    <div>
+                        <span class="callout">{annotations::marker(number)}</span>
+                        {" "}
+                        {annotation.note.as_str()}
+                    </div>
+                }).collect::<Html>()
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yew::virtual_dom::VNode;
+
+    use super::*;
+    use crate::engine::export::hostile::{self, Random};
+    use crate::engine::{brushes, highlight};
+
+    const CLASSES: [&str; 6] = [
+        "print-page",
+        "file-name",
+        "line-number",
+        "emphasized",
+        "callout",
+        "legend",
+    ];
+
+    /// The markup the browser ends up with, the classes checked on the way.
+    fn write(node: &Html, markup: &mut String) -> Result<(), String> {
+        match node {
+            VNode::VTag(tag) => {
+                markup.push_str(&format!("<{}", tag.tag()));
+                for (name, value) in tag.attributes.iter() {
+                    if name != "class" {
+                        return Err(format!("attribute `{name}`"));
+                    }
+                    for class in value.split_whitespace() {
+                        let known = CLASSES.contains(&class)
+                            || Emphasis::ALL
+                                .iter()
+                                .any(|emphasis| emphasis.css_class() == class)
+                            || crate::engine::theme::CLASSIC
+                                .styles
+                                .iter()
+                                .any(|(kind, _)| kind.css_class() == Some(class));
+                        if !known {
+                            return Err(format!("class `{class}`"));
+                        }
+                    }
+                    markup.push_str(&format!(" class=\"{}\"", html::escape(value)));
+                }
+                markup.push('>');
+                if let Some(children) = tag.children() {
+                    write(children, markup)?;
+                }
+                markup.push_str(&format!("</{}>", tag.tag()));
+            }
+            VNode::VText(text) => markup.push_str(&html::escape(&text.text)),
+            VNode::VList(list) => {
+                for child in list.iter() {
+                    write(child, markup)?;
+                }
+            }
+            _ => return Err("a node other than an element or text".to_string()),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn hostile_input_never_becomes_markup() {
+        let mut random = Random::default();
+        let brushes = brushes::all();
+
+        for _ in 0..200 {
+            let code = random.hostile();
+            let name = random.hostile();
+            let note = random.hostile();
+            let brush = &brushes[random.below(brushes.len())];
+            let stream = highlight(&code, brush);
+
+            let mut options = Options::default();
+            options.marks.toggle(1, Emphasis::ALL[random.below(4)]);
+            options.annotations.add(1, &note);
+            let files = [
+                File {
+                    name: &name,
+                    stream: &stream,
+                    options: options.clone(),
+                },
+                File {
+                    name: &note,
+                    stream: &stream,
+                    options,
+                },
+            ];
+
+            let mut markup = String::new();
+            let written = write(&view(&files, &Layout::default()), &mut markup)
+                .and_then(|()| hostile::html(&markup, &["section", "header"]));
+            if let Err(error) = written {
+                panic!("print of {code:?} named {name:?} noted {note:?}: {error}");
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::rc::Rc;
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::utils::snippets::{self, Snippet};
+
+#[derive(Properties)]
+pub struct Props {
+    pub snippets: Rc<Vec<Snippet>>,
+    /// Whether there is code to save.
+    pub can_save: bool,
+    /// Called with the name and tags to save the current code under.
+    pub on_save: Callback<(String, Vec<String>)>,
+    pub on_open: Callback<Snippet>,
+    pub on_duplicate: Callback<Snippet>,
+    /// Called with the `id` of the snippet to delete.
+    pub on_delete: Callback<u32>,
+    pub on_close: Callback<()>,
+    /// Shown below the snippets.
+    #[prop_or_default]
+    pub children: Html,
+}
+
+impl PartialEq for Props {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.snippets, &other.snippets)
+            && self.can_save == other.can_save
+            && self.on_save == other.on_save
+            && self.on_open == other.on_open
+            && self.on_duplicate == other.on_duplicate
+            && self.on_delete == other.on_delete
+            && self.on_close == other.on_close
+            && self.children == other.children
+    }
+}
+
+/// Side panel to save the current code and search, open, duplicate and
+/// delete saved snippets.
+pub struct SnippetLibrary {
+    query: String,
+    name: String,
+    tags: String,
+}
+
+pub enum Msg {
+    Search(String),
+    InputName(String),
+    InputTags(String),
+    Save,
+}
+
+impl SnippetLibrary {
+    fn view_snippet(ctx: &Context<Self>, snippet: &Snippet) -> Html {
+        let updated = js_sys::Date::new(&snippet.updated.into())
+            .to_locale_string("default", &Default::default());
+        let on_open = {
+            let snippet = snippet.clone();
+            ctx.props().on_open.reform(move |_| snippet.clone())
+        };
+        let on_duplicate = {
+            let snippet = snippet.clone();
+            ctx.props().on_duplicate.reform(move |_| snippet.clone())
+        };
+        let on_delete = {
+            let id = snippet.id;
+            let on_delete = ctx.props().on_delete.clone();
+            Callback::from(move |_: MouseEvent| {
+                if let Some(id) = id {
+                    on_delete.emit(id);
+                }
+            })
+        };
+
+        html! {
+            <li class="list-group-item px-0">
+                <div class="d-flex align-items-center">
+                    <a href="#" class="fw-bold flex-grow-1" onclick={on_open}>{snippet.name.as_str()}</a>
+                    <button type="button" class="btn btn-link btn-sm p-0 mb-0 ms-2" title="Duplicate" onclick={on_duplicate}>
+                        {"Copy"}
+                    </button>
+                    <button type="button" class="btn btn-link btn-sm text-danger p-0 mb-0 ms-2" title="Delete" onclick={on_delete}>
+                        {"Delete"}
+                    </button>
+                </div>
+                <div class="text-sm">
+                    if let Some(language) = &snippet.language {
+                        <span class="badge bg-dark me-1">{language.as_str()}</span>
+                    }
+                    {
+                        snippet.tags.iter().map(|tag| html! {
+                            <span class="badge bg-secondary me-1">{tag.as_str()}</span>
+                        }).collect::<Html>()
+                    }
+                    <span class="text-muted">{String::from(updated)}</span>
+                </div>
+            </li>
+        }
+    }
+}
+
+impl Component for SnippetLibrary {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        SnippetLibrary {
+            query: String::new(),
+            name: String::new(),
+            tags: String::new(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Search(query) => self.query = query,
+            Msg::InputName(name) => self.name = name,
+            Msg::InputTags(tags) => self.tags = tags,
+            Msg::Save => {
+                if self.name.trim().is_empty() {
+                    return false;
+                }
+
+                ctx.props().on_save.emit((
+                    self.name.trim().to_string(),
+                    snippets::parse_tags(&self.tags),
+                ));
+                self.name.clear();
+                self.tags.clear();
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let matching = ctx
+            .props()
+            .snippets
+            .iter()
+            .filter(|snippet| snippet.matches(&self.query))
+            .collect::<Vec<_>>();
+
+        html! {
+            <aside class="snippet-library card">
+                <div class="card-body">
+                    <div class="d-flex align-items-center mb-3">
+                        <h5 class="flex-grow-1 mb-0">{"Snippets"}</h5>
+                        <button type="button" class="btn-close" onclick={ctx.props().on_close.reform(|_| ())}>
+                            {"×"}
+                        </button>
+                    </div>
+                    <div class="input-group input-group-outline mb-2">
+                        <input
+                            type="text"
+                            class="form-control"
+                            placeholder="Name"
+                            value={self.name.clone()}
+                            oninput={ctx.link().callback(|e: InputEvent| Msg::InputName(e.target_unchecked_into::<HtmlInputElement>().value()))}
+                        />
+                    </div>
+                    <div class="d-flex mb-3">
+                        <div class="input-group input-group-outline">
+                            <input
+                                type="text"
+                                class="form-control"
+                                placeholder="Tags, comma separated"
+                                value={self.tags.clone()}
+                                oninput={ctx.link().callback(|e: InputEvent| Msg::InputTags(e.target_unchecked_into::<HtmlInputElement>().value()))}
+                            />
+                        </div>
+                        <button
+                            type="button"
+                            class="btn btn-dark mb-0 ms-2"
+                            disabled={!ctx.props().can_save || self.name.trim().is_empty()}
+                            onclick={ctx.link().callback(|_| Msg::Save)}
+                        >
+                            {"Save"}
+                        </button>
+                    </div>
+                    <div class="input-group input-group-outline mb-2">
+                        <input
+                            type="search"
+                            class="form-control"
+                            placeholder="Search by name, tag or language"
+                            value={self.query.clone()}
+                            oninput={ctx.link().callback(|e: InputEvent| Msg::Search(e.target_unchecked_into::<HtmlInputElement>().value()))}
+                        />
+                    </div>
+                    if matching.is_empty() {
+                        <p class="text-sm text-muted">
+                            {if ctx.props().snippets.is_empty() {"No snippets yet"} else {"No matching snippets"}}
+                        </p>
+                    } else {
+                        <ul class="list-group list-group-flush">
+                            { matching.into_iter().map(|snippet| Self::view_snippet(ctx, snippet)).collect::<Html>() }
+                        </ul>
+                    }
+                    { ctx.props().children.clone() }
+                </div>
+            </aside>
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::rc::Rc;
+
+use web_sys::{Element, HtmlElement};
+use yew::prelude::*;
+
+use crate::engine::annotations::{self, Annotations};
+use crate::engine::marks::LineMarks;
+use crate::engine::token::{Line, TokenStream};
+
+/// Height of a row in px, has to match `.dp-highlighter.virtual .line`.
+const LINE_HEIGHT: f64 = 14.0;
+/// Rows rendered above and below the viewport so scrolling doesn't flash.
+const OVERSCAN: usize = 20;
+const DEFAULT_VIEWPORT_HEIGHT: f64 = 600.0;
+
+#[derive(Properties)]
+pub struct Props {
+    pub stream: Rc<TokenStream>,
+    #[prop_or_default]
+    pub marks: LineMarks,
+    #[prop_or_default]
+    pub annotations: Annotations,
+    /// Called with the 1 based number of a line when its gutter is clicked.
+    #[prop_or_default]
+    pub on_gutter_click: Callback<usize>,
+}
+
+impl PartialEq for Props {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.stream, &other.stream)
+            && self.marks == other.marks
+            && self.annotations == other.annotations
+            && self.on_gutter_click == other.on_gutter_click
+    }
+}
+
+/// Preview which only keeps the rows inside the viewport in the DOM, so
+/// documents with tens of thousands of lines stay responsive.
+pub struct VirtualPreview {
+    container_ref: NodeRef,
+    scroll_top: f64,
+    viewport_height: f64,
+}
+
+pub enum Msg {
+    Scroll,
+}
+
+impl VirtualPreview {
+    fn measure(&mut self) -> bool {
+        let Some(container) = self.container_ref.cast::<Element>() else {
+            return false;
+        };
+
+        let scroll_top = container.scroll_top() as f64;
+        let viewport_height = container.client_height() as f64;
+        let changed = scroll_top != self.scroll_top || viewport_height != self.viewport_height;
+
+        self.scroll_top = scroll_top;
+        if viewport_height > 0.0 {
+            self.viewport_height = viewport_height;
+        }
+
+        changed
+    }
+
+    /// The rows from the first to the last rendered one.
+    fn window(&self, ctx: &Context<Self>) -> (usize, usize) {
+        let lines = ctx.props().stream.lines.len();
+        let visible_rows = (self.viewport_height / LINE_HEIGHT).ceil() as usize + 2 * OVERSCAN;
+        let first = ((self.scroll_top / LINE_HEIGHT) as usize)
+            .saturating_sub(OVERSCAN)
+            .min(lines);
+
+        (first, (first + visible_rows).min(lines))
+    }
+
+    /// Sizes the rows and moves the window through custom properties of the
+    /// container, style attributes being refused by the
+    /// Content-Security-Policy.
+    fn lay_out(&self, ctx: &Context<Self>) {
+        let Some(container) = self.container_ref.cast::<HtmlElement>() else {
+            return;
+        };
+
+        let lines = ctx.props().stream.lines.len();
+        let (first, _) = self.window(ctx);
+        let style = container.style();
+        let properties = [
+            ("--gutter-width", format!("{}ch", lines.to_string().len())),
+            ("--rows-height", format!("{}px", lines as f64 * LINE_HEIGHT)),
+            ("--window-top", format!("{}px", first as f64 * LINE_HEIGHT)),
+        ];
+        for (name, value) in properties {
+            let _ = style.set_property(name, &value);
+        }
+    }
+
+    fn view_line(ctx: &Context<Self>, index: usize, line: &Line) -> Html {
+        let number = index + 1;
+        let emphasis = ctx
+            .props()
+            .marks
+            .get(number)
+            .map(|emphasis| emphasis.css_class());
+        let on_gutter_click = ctx.props().on_gutter_click.reform(move |_| number);
+        let callouts = ctx.props().annotations.numbers_on(number);
+
+        html! {
+            <div key={index} class={classes!("line", index.is_multiple_of(2).then_some("alt"), emphasis)}>
+                <span class="gutter" onclick={on_gutter_click}>{number}</span>
+                <span class="content">
+                    {
+                        line.tokens.iter().map(|token| {
+                            match token.kind.css_class() {
+                                Some(class) => html! { <span class={class}>{token.text.as_str()}</span> },
+                                None => html! { {token.text.as_str()} },
+                            }
+                        }).collect::<Html>()
+                    }
+                    if !callouts.is_empty() {
+                        <span class="callout">{annotations::markers(&callouts)}</span>
+                    }
+                </span>
+            </div>
+        }
+    }
+}
+
+impl Component for VirtualPreview {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        VirtualPreview {
+            container_ref: NodeRef::default(),
+            scroll_top: 0.0,
+            viewport_height: DEFAULT_VIEWPORT_HEIGHT,
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
+        self.lay_out(ctx);
+        if first_render && self.measure() {
+            ctx.link().send_message(Msg::Scroll);
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Scroll => self.measure(),
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let lines = &ctx.props().stream.lines;
+        let (first, last) = self.window(ctx);
+
+        html! {
+            <div
+                ref={self.container_ref.clone()}
+                class="dp-highlighter virtual"
+                onscroll={ctx.link().callback(|_| Msg::Scroll)}
+            >
+                <div class="rows">
+                    <div class="window">
+                        {
+                            lines[first..last].iter().enumerate().map(|(offset, line)| {
+                                Self::view_line(ctx, first + offset, line)
+                            }).collect::<Html>()
+                        }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Numbered notes attached to lines, rendered as circled callouts at the end
+//! of the line and as a legend below the code.
+
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    /// 1 based line the note points at.
+    pub line: usize,
+    pub note: String,
+}
+
+/// Notes ordered by line, their callout number is their position plus one.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Annotations {
+    notes: Vec<Annotation>,
+}
+
+impl Annotations {
+    /// Adds a note after any other note on the same line.
+    pub fn add(&mut self, line: usize, note: &str) {
+        let position = self
+            .notes
+            .partition_point(|annotation| annotation.line <= line);
+
+        self.notes.insert(
+            position,
+            Annotation {
+                line,
+                note: note.trim().to_string(),
+            },
+        );
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.notes.len() {
+            self.notes.remove(index);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// Every note with its callout number.
+    pub fn numbered(&self) -> impl Iterator<Item = (usize, &Annotation)> {
+        self.notes
+            .iter()
+            .enumerate()
+            .map(|(index, annotation)| (index + 1, annotation))
+    }
+
+    /// Callout numbers of the notes on `line`.
+    pub fn numbers_on(&self, line: usize) -> Vec<usize> {
+        self.numbered()
+            .filter(|(_, annotation)| annotation.line == line)
+            .map(|(number, _)| number)
+            .collect()
+    }
+}
+
+/// The circled number for a callout, falling back to `(n)` past fifty.
+pub fn marker(number: usize) -> String {
+    let circled = match number {
+        1..=20 => char::from_u32(0x2460 + number as u32 - 1),
+        21..=35 => char::from_u32(0x3251 + number as u32 - 21),
+        36..=50 => char::from_u32(0x32b1 + number as u32 - 36),
+        _ => None,
+    };
+
+    circled
+        .map(String::from)
+        .unwrap_or_else(|| format!("({number})"))
+}
+
+/// All callouts of `line`, separated by spaces.
+pub fn markers(numbers: &[usize]) -> String {
+    numbers
+        .iter()
+        .map(|number| marker(*number))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,176 @@
+//! Guesses the language of a snippet from its content, for files whose
+//! extension doesn't tell.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// Below this score no language is guessed at all.
+const MIN_SCORE: usize = 3;
+/// Occurrences of a hint beyond this don't add to the score, so one long
+/// repetitive file can't outweigh every other hint.
+const MAX_OCCURRENCES: usize = 3;
+
+struct Hint {
+    /// Alias of the brush, as used by [`super::brushes::find`].
+    language: &'static str,
+    regex: Regex,
+    weight: usize,
+}
+
+fn hints() -> &'static [Hint] {
+    static HINTS: OnceLock<Vec<Hint>> = OnceLock::new();
+
+    HINTS.get_or_init(|| {
+        [
+            ("php", r"<\?php", 10),
+            ("xml", r"\A\s*<(\?xml|!DOCTYPE|html|[A-Za-z][\w:.-]*[\s/>])", 5),
+            ("xml", r"</[A-Za-z][\w:.-]*>", 1),
+            ("python", r"(?m)^\s*def \w+\(.*\)\s*(->\s*[^:]+)?:\s*$", 3),
+            ("python", r"(?m)^\s*class \w+(\(.*\))?:\s*$", 3),
+            ("python", r"(?m)^\s*(from [\w.]+ )?import [\w.]+(, [\w.]+)*\s*$", 2),
+            ("python", r"\belif\b|\bself\.|__\w+__", 2),
+            ("python", r"(?m)\A#!.*python", 10),
+            ("ruby", r"(?m)^\s*end\s*$", 1),
+            ("ruby", r"(?m)^\s*def \w+[!?]?(\(.*\))?\s*$", 2),
+            ("ruby", r"\bputs\b|\brequire\s+['\x22]|\.each\s+do\b|\bdo\s*\|", 3),
+            ("ruby", r"(?m)\A#!.*ruby", 10),
+            ("java", r"\bpublic\s+(static\s+)?(final\s+)?(class|interface|enum)\b", 2),
+            ("java", r"(?m)^import java\.|System\.out\.print|String\[\]\s+args", 5),
+            ("java", r"@Override\b", 2),
+            ("csharp", r"(?m)^using System", 5),
+            ("csharp", r"\bnamespace\s+[\w.]+|Console\.Write", 3),
+            ("csharp", r"\{\s*get;\s*(private\s+)?set;\s*\}", 4),
+            ("cpp", r"(?m)^\s*#\s*(include|define|ifndef|pragma)\b", 5),
+            ("cpp", r"\bstd::|\bcout\s*<<|\btemplate\s*<", 3),
+            ("cpp", r"\bint\s+main\s*\(", 2),
+            ("js", r"\bfunction\s*\w*\s*\(|=>", 2),
+            ("js", r"\b(const|let|var)\s+\w+\s*=", 1),
+            ("js", r"console\.log|document\.|window\.|require\(\s*['\x22]", 4),
+            ("js", r"(?m)^\s*export\s+(default|const|function|class)\b", 3),
+            ("js", r"(?m)\A#!.*node", 10),
+            ("css", r"(?m)^\s*[.#]?[\w-]+([\s,>+~]+[.#:]?[\w-]+)*\s*\{\s*$", 2),
+            ("css", r"(?m)^\s*[\w-]+\s*:\s*[^;{}]+;\s*$", 1),
+            ("css", r"@media\b|@import\b|!important", 3),
+            ("sql", r"(?i)\bselect\b[\s\S]+?\bfrom\b", 4),
+            ("sql", r"(?i)\b(insert\s+into|create\s+table|update\s+\w+\s+set|delete\s+from|alter\s+table)\b", 5),
+            ("vb", r"(?im)^\s*(dim|(end\s+)?sub|(end\s+)?function)\b", 3),
+            ("vb", r"(?i)\bas\s+(string|integer|boolean|object|long)\b", 3),
+            ("delphi", r"(?i)\bprocedure\b|\bwriteln\b", 3),
+            ("delphi", r"(?im)^\s*(unit|program)\s+\w+;", 5),
+            ("delphi", r"(?im)^\s*end[;.]\s*$", 2),
+            ("delphi", r":=", 1),
+        ]
+        .into_iter()
+        .map(|(language, regex, weight)| Hint {
+            language,
+            regex: Regex::new(regex).unwrap(),
+            weight,
+        })
+        .collect()
+    })
+}
+
+/// The brush alias of the language `code` most likely is written in, `None`
+/// if no language stands out.
+pub fn language(code: &str) -> Option<&'static str> {
+    let mut scores: Vec<(&'static str, usize)> = Vec::new();
+
+    for hint in hints() {
+        let occurrences = hint.regex.find_iter(code).take(MAX_OCCURRENCES).count();
+        if occurrences == 0 {
+            continue;
+        }
+
+        match scores
+            .iter_mut()
+            .find(|(language, _)| *language == hint.language)
+        {
+            Some((_, score)) => *score += hint.weight * occurrences,
+            None => scores.push((hint.language, hint.weight * occurrences)),
+        }
+    }
+
+    scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    match scores[..] {
+        [(_, first), (_, second), ..] if first == second => None,
+        [(language, score), ..] if score >= MIN_SCORE => Some(language),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::brushes;
+
+    #[test]
+    fn guesses_every_language() {
+        let snippets = [
+            ("php", "<?php\necho 'hi';\n?>"),
+            (
+                "xml",
+                "<?xml version=\"1.0\"?>\n<note><to>Tove</to></note>",
+            ),
+            (
+                "python",
+                "import os\n\ndef main():\n    print(os.getcwd())\n",
+            ),
+            (
+                "ruby",
+                "require 'json'\n\ndef greet\n  puts 'hi'\nend\n",
+            ),
+            (
+                "java",
+                "public class Main {\n    public static void main(String[] args) {\n        System.out.println(1);\n    }\n}",
+            ),
+            (
+                "csharp",
+                "using System;\n\nnamespace App {\n    class P { static void Main() { Console.WriteLine(1); } }\n}",
+            ),
+            (
+                "cpp",
+                "#include <iostream>\n\nint main() {\n    std::cout << 1;\n}",
+            ),
+            (
+                "js",
+                "const add = (a, b) => a + b;\nconsole.log(add(1, 2));",
+            ),
+            (
+                "css",
+                "body {\n  margin: 0;\n  color: red;\n}\n@media print {\n}",
+            ),
+            ("sql", "SELECT name FROM users WHERE id = 1;"),
+            (
+                "vb",
+                "Sub Main()\n    Dim name As String\nEnd Sub",
+            ),
+            (
+                "delphi",
+                "program Hello;\nbegin\n  writeln('Hello');\nend.",
+            ),
+        ];
+
+        for (expected, code) in snippets {
+            assert_eq!(language(code), Some(expected), "{code}");
+        }
+    }
+
+    #[test]
+    fn guesses_nothing_without_a_clear_winner() {
+        assert_eq!(language(""), None);
+        assert_eq!(language("   \n\n"), None);
+        assert_eq!(language("Just a sentence, no code at all."), None);
+        // A single weak hint stays below the minimum score.
+        assert_eq!(language("x := 1"), None);
+        // Python and Ruby are as likely.
+        assert_eq!(language("def f():\nputs"), None);
+    }
+
+    #[test]
+    fn hints_name_brushes() {
+        for hint in hints() {
+            assert!(brushes::find(hint.language).is_some(), "{}", hint.language);
+        }
+    }
+}
@@ -0,0 +1,549 @@
+//! Line and word level diff of two highlighted snippets.
+
+use std::ops::Range;
+
+use super::token::{Line, TokenKind, TokenStream};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Shortest edit script turning `a` into `b`, using Myers' O(ND) algorithm in
+/// its linear space variant.
+pub fn diff<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    let mut edits = Vec::with_capacity(a.len().max(b.len()));
+    compare(a, b, (0, 0), &mut edits);
+    edits
+}
+
+/// Appends the edits of `a` and `b`, which start at `origin` of the whole
+/// sequences, to `edits`.
+fn compare<T: PartialEq>(a: &[T], b: &[T], origin: (usize, usize), edits: &mut Vec<Edit>) {
+    let (x0, y0) = origin;
+    let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (middle_a, middle_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let (x, y) = (x0 + prefix, y0 + prefix);
+
+    edits.extend((0..prefix).map(|i| Edit::Equal(x0 + i, y0 + i)));
+    let split = if middle_a.is_empty() || middle_b.is_empty() {
+        None
+    } else {
+        middle_snake(middle_a, middle_b)
+    };
+    match split {
+        Some((split_x, split_y)) => {
+            compare(&middle_a[..split_x], &middle_b[..split_y], (x, y), edits);
+            compare(
+                &middle_a[split_x..],
+                &middle_b[split_y..],
+                (x + split_x, y + split_y),
+                edits,
+            );
+        }
+        None => {
+            edits.extend((0..middle_a.len()).map(|i| Edit::Delete(x + i)));
+            edits.extend((0..middle_b.len()).map(|i| Edit::Insert(y + i)));
+        }
+    }
+    edits.extend(
+        (0..suffix).map(|i| Edit::Equal(a.len() - suffix + i + x0, b.len() - suffix + i + y0)),
+    );
+}
+
+/// Where the forward and the reverse search of the D-paths of `a` and `b`
+/// meet, which splits a shortest edit script into two halves. Only the
+/// furthest point of every diagonal is kept, so the memory grows with N + M
+/// rather than with D².
+fn middle_snake<T: PartialEq>(a: &[T], b: &[T]) -> Option<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    let index = |k: isize| (offset + k) as usize;
+
+    // The furthest x reached on every diagonal k = x - y, counted from the
+    // start forward and from the end backward, -1 where none was reached.
+    let mut forward = vec![-1isize; 2 * offset as usize + 1];
+    let mut backward = forward.clone();
+    forward[index(1)] = 0;
+    backward[index(1)] = 0;
+
+    let delta = n - m;
+    // With an odd delta the forward paths meet the backward ones of the
+    // previous round, with an even one the other way round.
+    let odd = delta % 2 != 0;
+    // Diagonals that ran past an edge are left out of later rounds.
+    let (mut forward_start, mut forward_end) = (0, 0);
+    let (mut backward_start, mut backward_end) = (0, 0);
+
+    for d in 0..max {
+        for k in (-d + forward_start..=d - forward_end).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[index(k - 1)] < forward[index(k + 1)]) {
+                forward[index(k + 1)]
+            } else {
+                forward[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[index(k)] = x;
+
+            if x > n {
+                forward_end += 2;
+            } else if y > m {
+                forward_start += 2;
+            } else if odd {
+                let reverse = delta - k;
+                if reverse.abs() <= max
+                    && backward[index(reverse)] != -1
+                    && x >= n - backward[index(reverse)]
+                {
+                    return Some((x as usize, y as usize));
+                }
+            }
+        }
+
+        for k in (-d + backward_start..=d - backward_end).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[index(k - 1)] < backward[index(k + 1)]) {
+                backward[index(k + 1)]
+            } else {
+                backward[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[index(k)] = x;
+
+            if x > n {
+                backward_end += 2;
+            } else if y > m {
+                backward_start += 2;
+            } else if !odd {
+                let ahead = delta - k;
+                if ahead.abs() <= max && forward[index(ahead)] != -1 {
+                    let forward_x = forward[index(ahead)];
+                    if forward_x >= n - x {
+                        return Some((forward_x as usize, (forward_x - ahead) as usize));
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    Equal,
+    Removed,
+    Added,
+    Changed,
+}
+
+/// A row of the side-by-side view, pointing at lines of either version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Row {
+    pub before: Option<usize>,
+    pub after: Option<usize>,
+    pub change: Change,
+}
+
+/// A piece of a line with a single token kind, `marked` when it is part of
+/// a word level change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub marked: bool,
+}
+
+pub struct Diff {
+    pub before: TokenStream,
+    pub after: TokenStream,
+    pub rows: Vec<Row>,
+}
+
+impl Diff {
+    pub fn new(before: TokenStream, after: TokenStream) -> Self {
+        let before_lines = before.lines.iter().map(Line::text).collect::<Vec<_>>();
+        let after_lines = after.lines.iter().map(Line::text).collect::<Vec<_>>();
+        let rows = side_by_side(&diff(&before_lines, &after_lines));
+
+        Diff {
+            before,
+            after,
+            rows,
+        }
+    }
+
+    /// The segments to render on either side of `row`.
+    pub fn segments(&self, row: &Row) -> (Vec<Segment<'_>>, Vec<Segment<'_>>) {
+        let before = row.before.map(|index| &self.before.lines[index]);
+        let after = row.after.map(|index| &self.after.lines[index]);
+
+        match (before, after) {
+            (Some(before), Some(after)) if row.change == Change::Changed => {
+                let (removed, added) = changed_ranges(&before.text(), &after.text());
+
+                (segments(before, &removed), segments(after, &added))
+            }
+            _ => (
+                before.map(|line| segments(line, &[])).unwrap_or_default(),
+                after.map(|line| segments(line, &[])).unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+/// Pairs up removed and inserted lines of each hunk as changed rows.
+fn side_by_side(edits: &[Edit]) -> Vec<Row> {
+    let mut rows = Vec::with_capacity(edits.len());
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    let flush = |rows: &mut Vec<Row>, removed: &mut Vec<usize>, added: &mut Vec<usize>| {
+        for i in 0..removed.len().max(added.len()) {
+            let before = removed.get(i).copied();
+            let after = added.get(i).copied();
+            let change = match (before, after) {
+                (Some(_), Some(_)) => Change::Changed,
+                (Some(_), None) => Change::Removed,
+                _ => Change::Added,
+            };
+
+            rows.push(Row {
+                before,
+                after,
+                change,
+            });
+        }
+        removed.clear();
+        added.clear();
+    };
+
+    for edit in edits {
+        match *edit {
+            Edit::Equal(before, after) => {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push(Row {
+                    before: Some(before),
+                    after: Some(after),
+                    change: Change::Equal,
+                });
+            }
+            Edit::Delete(before) => removed.push(before),
+            Edit::Insert(after) => added.push(after),
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+
+    rows
+}
+
+/// Byte ranges of the words which differ between two versions of a line.
+pub fn changed_ranges(before: &str, after: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let before_words = words(before);
+    let after_words = words(after);
+    let before_text = before_words
+        .iter()
+        .map(|range| &before[range.clone()])
+        .collect::<Vec<_>>();
+    let after_text = after_words
+        .iter()
+        .map(|range| &after[range.clone()])
+        .collect::<Vec<_>>();
+
+    let mut removed: Vec<Range<usize>> = Vec::new();
+    let mut added: Vec<Range<usize>> = Vec::new();
+
+    for edit in diff(&before_text, &after_text) {
+        match edit {
+            Edit::Delete(index) => push_range(&mut removed, before_words[index].clone()),
+            Edit::Insert(index) => push_range(&mut added, after_words[index].clone()),
+            Edit::Equal(..) => {}
+        }
+    }
+
+    (removed, added)
+}
+
+fn push_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+/// Splits a line into runs of word characters, runs of whitespace and
+/// single punctuation characters.
+fn words(line: &str) -> Vec<Range<usize>> {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+
+    let mut words: Vec<Range<usize>> = Vec::new();
+    let mut previous = None;
+
+    for (index, c) in line.char_indices() {
+        let current = class(c);
+
+        match words.last_mut() {
+            Some(last) if previous == Some(current) && current != 2 => {
+                last.end = index + c.len_utf8()
+            }
+            _ => words.push(index..index + c.len_utf8()),
+        }
+        previous = Some(current);
+    }
+
+    words
+}
+
+/// Splits the tokens of `line` at the boundaries of `ranges`.
+pub fn segments<'a>(line: &'a Line, ranges: &[Range<usize>]) -> Vec<Segment<'a>> {
+    let mut segments = Vec::with_capacity(line.tokens.len());
+    let mut offset = 0;
+
+    for token in &line.tokens {
+        let start = offset;
+        let end = offset + token.text.len();
+        let mut position = start;
+
+        let mut cuts = ranges
+            .iter()
+            .flat_map(|range| [range.start, range.end])
+            .filter(|cut| *cut > start && *cut < end)
+            .collect::<Vec<_>>();
+        cuts.push(end);
+
+        for cut in cuts {
+            if cut <= position {
+                continue;
+            }
+
+            let marked = ranges
+                .iter()
+                .any(|range| range.start <= position && position < range.end);
+            segments.push(Segment {
+                kind: token.kind,
+                text: &token.text[position - start..cut - start],
+                marked,
+            });
+            position = cut;
+        }
+
+        offset = end;
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::export::hostile::Random;
+    use crate::engine::token::Token;
+
+    fn stream(text: &str) -> TokenStream {
+        TokenStream::from_tokens(vec![Token::new(TokenKind::Plain, text)])
+    }
+
+    /// `b` rebuilt from `a` and the edits, which have to be in order.
+    fn apply<T: Copy + PartialEq + std::fmt::Debug>(a: &[T], b: &[T], edits: &[Edit]) -> Vec<T> {
+        let (mut x, mut y) = (0, 0);
+        let mut result = Vec::new();
+        for edit in edits {
+            match *edit {
+                Edit::Equal(before, after) => {
+                    assert_eq!((before, after), (x, y));
+                    assert_eq!(a[before], b[after]);
+                    result.push(a[before]);
+                    x += 1;
+                    y += 1;
+                }
+                Edit::Delete(before) => {
+                    assert_eq!(before, x);
+                    x += 1;
+                }
+                Edit::Insert(after) => {
+                    assert_eq!(after, y);
+                    result.push(b[after]);
+                    y += 1;
+                }
+            }
+        }
+        assert_eq!((x, y), (a.len(), b.len()));
+        result
+    }
+
+    #[test]
+    fn diffs_the_edge_cases() {
+        assert_eq!(diff::<&str>(&[], &[]), []);
+        assert_eq!(
+            diff(&["a", "b"], &["a", "b"]),
+            [Edit::Equal(0, 0), Edit::Equal(1, 1)]
+        );
+        assert_eq!(diff(&[], &["a", "b"]), [Edit::Insert(0), Edit::Insert(1)]);
+        assert_eq!(diff(&["a", "b"], &[]), [Edit::Delete(0), Edit::Delete(1)]);
+    }
+
+    #[test]
+    fn finds_a_shortest_edit_script() {
+        let a = "abcabba"
+            .split("")
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<_>>();
+        let b = "cbabac"
+            .split("")
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<_>>();
+        let edits = diff(&a, &b);
+
+        assert_eq!(apply(&a, &b, &edits), b);
+        // The example of Myers' paper, 5 edits apart.
+        let changes = edits
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Equal(..)))
+            .count();
+        assert_eq!(changes, 5);
+    }
+
+    /// Edits of a shortest script, from the longest common subsequence.
+    fn fewest_changes(a: &[u8], b: &[u8]) -> usize {
+        let mut lengths = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                lengths[i + 1][j + 1] = if x == y {
+                    lengths[i][j] + 1
+                } else {
+                    lengths[i][j + 1].max(lengths[i + 1][j])
+                };
+            }
+        }
+        a.len() + b.len() - 2 * lengths[a.len()][b.len()]
+    }
+
+    #[test]
+    fn finds_shortest_scripts_of_random_sequences() {
+        let mut random = Random::default();
+
+        for _ in 0..500 {
+            let sequence = |random: &mut Random| {
+                let length = random.below(30);
+                (0..length)
+                    .map(|_| b"abc"[random.below(3)])
+                    .collect::<Vec<_>>()
+            };
+            let (a, b) = (sequence(&mut random), sequence(&mut random));
+            let edits = diff(&a, &b);
+
+            assert_eq!(apply(&a, &b, &edits), b, "{a:?} {b:?}");
+            let changes = edits
+                .iter()
+                .filter(|edit| !matches!(edit, Edit::Equal(..)))
+                .count();
+            assert_eq!(changes, fewest_changes(&a, &b), "{a:?} {b:?}");
+        }
+    }
+
+    #[test]
+    fn diffs_long_unrelated_inputs() {
+        let a = (0..5000).collect::<Vec<_>>();
+        let b = (5000..10_000).collect::<Vec<_>>();
+
+        let edits = diff(&a, &b);
+        assert_eq!(edits.len(), 10_000);
+        assert_eq!(apply(&a, &b, &edits), b);
+    }
+
+    #[test]
+    fn pairs_removed_and_added_lines() {
+        let diff = Diff::new(stream("a\nb\nc\nd"), stream("a\nB\nd\ne"));
+        let rows = diff
+            .rows
+            .iter()
+            .map(|row| (row.before, row.after, row.change))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            rows,
+            [
+                (Some(0), Some(0), Change::Equal),
+                (Some(1), Some(1), Change::Changed),
+                (Some(2), None, Change::Removed),
+                (Some(3), Some(2), Change::Equal),
+                (None, Some(3), Change::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn sees_a_trailing_newline_as_an_added_line() {
+        let diff = Diff::new(stream("a\nb"), stream("a\nb\n"));
+
+        assert_eq!(
+            diff.rows.last(),
+            Some(&Row {
+                before: None,
+                after: Some(2),
+                change: Change::Added,
+            })
+        );
+        assert_eq!(diff.rows.len(), 3);
+    }
+
+    #[test]
+    fn marks_the_changed_words() {
+        let (removed, added) = changed_ranges("let x = 1;", "let yy = 2;");
+        assert_eq!(removed, [4..5, 8..9]);
+        assert_eq!(added, [4..6, 9..10]);
+
+        assert_eq!(changed_ranges("", ""), (vec![], vec![]));
+        assert_eq!(changed_ranges("x", "a x b"), (vec![], vec![0..2, 3..5]));
+    }
+
+    #[test]
+    fn splits_tokens_at_the_changed_words() {
+        let line = Line {
+            tokens: vec![
+                Token::new(TokenKind::Keyword, "let"),
+                Token::new(TokenKind::Plain, " yy = 2;"),
+            ],
+        };
+        let segments = segments(&line, &[4..6, 9..10])
+            .into_iter()
+            .map(|segment| (segment.kind, segment.text, segment.marked))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            segments,
+            [
+                (TokenKind::Keyword, "let", false),
+                (TokenKind::Plain, " ", false),
+                (TokenKind::Plain, "yy", true),
+                (TokenKind::Plain, " = ", false),
+                (TokenKind::Plain, "2", true),
+                (TokenKind::Plain, ";", false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Colored text for terminals, using escape sequences of the palette the
+//! terminal supports.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::scene::width;
+use super::{File, Options};
+use crate::engine::annotations;
+use crate::engine::theme::{Color, Style, Theme};
+use crate::engine::token::TokenStream;
+
+const RESET: &str = "\x1b[0m";
+
+/// The colors of xterm, which most terminals start from.
+const BASIC: [Color; 16] = [
+    Color::rgb(0x000000),
+    Color::rgb(0xcd0000),
+    Color::rgb(0x00cd00),
+    Color::rgb(0xcdcd00),
+    Color::rgb(0x0000ee),
+    Color::rgb(0xcd00cd),
+    Color::rgb(0x00cdcd),
+    Color::rgb(0xe5e5e5),
+    Color::rgb(0x7f7f7f),
+    Color::rgb(0xff0000),
+    Color::rgb(0x00ff00),
+    Color::rgb(0xffff00),
+    Color::rgb(0x5c5cff),
+    Color::rgb(0xff00ff),
+    Color::rgb(0x00ffff),
+    Color::rgb(0xffffff),
+];
+/// Channel values of the 6×6×6 color cube of the 256 color palette.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// The 16 basic colors, which every terminal has.
+    Basic,
+    /// The 256 colors of xterm.
+    Extended,
+    /// 24 bit colors.
+    #[default]
+    TrueColor,
+}
+
+impl fmt::Display for ColorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ColorMode::Basic => "16",
+            ColorMode::Extended => "256",
+            ColorMode::TrueColor => "truecolor",
+        })
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode.to_ascii_lowercase().as_str() {
+            "16" | "basic" => Ok(ColorMode::Basic),
+            "256" | "extended" => Ok(ColorMode::Extended),
+            "truecolor" | "24bit" => Ok(ColorMode::TrueColor),
+            _ => Err(format!(
+                "unknown color mode `{mode}`, expected 16, 256 or truecolor"
+            )),
+        }
+    }
+}
+
+fn distance(a: Color, b: Color) -> u32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel(a.r, b.r) + channel(a.g, b.g) + channel(a.b, b.b)
+}
+
+/// Index of the basic color closest to `color`.
+pub fn nearest_basic(color: Color) -> u8 {
+    (0..BASIC.len())
+        .min_by_key(|&index| distance(color, BASIC[index]))
+        .unwrap_or(0) as u8
+}
+
+/// Index of the color of the cube or the gray ramp closest to `color`. The
+/// basic colors at 0 to 15 are left out as terminals change them.
+pub fn nearest_extended(color: Color) -> u8 {
+    let level = |channel: u8| match channel {
+        0..=47 => 0,
+        48..=114 => 1,
+        channel => (channel - 35) / 40,
+    };
+    let (r, g, b) = (level(color.r), level(color.g), level(color.b));
+    let cube = Color {
+        r: CUBE[r as usize],
+        g: CUBE[g as usize],
+        b: CUBE[b as usize],
+    };
+
+    let average = (color.r as u32 + color.g as u32 + color.b as u32) / 3;
+    let step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let value = 8 + 10 * step;
+    let gray = Color {
+        r: value,
+        g: value,
+        b: value,
+    };
+
+    if distance(color, gray) < distance(color, cube) {
+        232 + step
+    } else {
+        16 + 36 * r + 6 * g + b
+    }
+}
+
+/// The parameters selecting `color` as foreground, or background if
+/// `background` is set.
+fn color_codes(color: Color, mode: ColorMode, background: bool) -> String {
+    match mode {
+        ColorMode::Basic => {
+            let index = nearest_basic(color);
+            let base = match (background, index < 8) {
+                (false, true) => 30,
+                (false, false) => 90 - 8,
+                (true, true) => 40,
+                (true, false) => 100 - 8,
+            };
+            (base + index as u32).to_string()
+        }
+        ColorMode::Extended => format!(
+            "{};5;{}",
+            if background { 48 } else { 38 },
+            nearest_extended(color)
+        ),
+        ColorMode::TrueColor => format!(
+            "{};2;{};{};{}",
+            if background { 48 } else { 38 },
+            color.r,
+            color.g,
+            color.b
+        ),
+    }
+}
+
+/// `text` in `style`, followed by a reset.
+fn run(style: &Style, text: &str, background: Option<Color>, mode: ColorMode) -> String {
+    let mut codes = Vec::with_capacity(4);
+
+    if style.bold {
+        codes.push("1".to_string());
+    }
+    if style.italic {
+        codes.push("3".to_string());
+    }
+    codes.push(color_codes(style.color, mode, false));
+    if let Some(background) = background {
+        codes.push(color_codes(background, mode, true));
+    }
+
+    format!("\x1b[{}m{}{RESET}", codes.join(";"), escape(text))
+}
+
+/// Renders `stream` with the colors of `theme`, downgraded to what `mode`
+/// supports.
+pub fn render(stream: &TokenStream, theme: &Theme, options: &Options, mode: ColorMode) -> String {
+    let gutter_width = options.gutter_width(stream.lines.len());
+    // Backgrounds of marked lines reach as far as the longest line.
+    let widest = stream
+        .lines
+        .iter()
+        .map(|line| width(&line.text()))
+        .max()
+        .unwrap_or(0);
+    let mut ansi = String::new();
+
+    for (index, line) in stream.lines.iter().enumerate() {
+        let emphasis = options.marks.get(index + 1);
+        let background = emphasis.and_then(|emphasis| theme.emphasis_background(emphasis));
+
+        if options.line_numbers {
+            let line_number = Style {
+                color: theme.line_number,
+                ..Style::plain(0)
+            };
+            ansi.push_str(&run(
+                &line_number,
+                &format!(
+                    "{number:>gutter_width$} ",
+                    number = options.first_line + index
+                ),
+                None,
+                mode,
+            ));
+        }
+
+        for token in &line.tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+
+            let style = theme.emphasized_style(token.kind, emphasis);
+            ansi.push_str(&run(&style, &token.text, background, mode));
+        }
+
+        if background.is_some() {
+            let padding = widest.saturating_sub(width(&line.text()));
+            if padding > 0 {
+                ansi.push_str(&run(&theme.text, &" ".repeat(padding), background, mode));
+            }
+        }
+
+        let callouts = options.annotations.numbers_on(index + 1);
+        if !callouts.is_empty() {
+            ansi.push_str(&run(
+                &theme.callout,
+                &format!(" {}", annotations::markers(&callouts)),
+                None,
+                mode,
+            ));
+        }
+
+        ansi.push('\n');
+    }
+
+    for (index, (number, annotation)) in options.annotations.numbered().enumerate() {
+        if index == 0 {
+            ansi.push('\n');
+        }
+        ansi.push_str(&run(
+            &theme.callout,
+            &annotations::marker(number),
+            None,
+            mode,
+        ));
+        ansi.push(' ');
+        ansi.push_str(&run(&theme.text, &annotation.note, None, mode));
+        ansi.push('\n');
+    }
+
+    ansi
+}
+
+/// Renders several files one after the other, each below a bold file name.
+pub fn render_files(files: &[File], theme: &Theme, mode: ColorMode) -> String {
+    files
+        .iter()
+        .map(|file| {
+            let name = Style {
+                bold: true,
+                ..theme.text
+            };
+            format!(
+                "{}\n{}",
+                run(&name, file.name, None, mode),
+                render(file.stream, theme, &file.options, mode)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Replaces control characters, which could move the cursor or change the
+/// colors of the terminal.
+pub fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_control() && c != '\t' {
+                char::REPLACEMENT_CHARACTER
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::theme::CLASSIC;
+    use crate::engine::token::{Token, TokenKind};
+
+    #[test]
+    fn basic_colors_are_matched_exactly() {
+        for (index, color) in BASIC.iter().enumerate() {
+            assert_eq!(nearest_basic(*color), index as u8);
+        }
+        assert_eq!(nearest_basic(Color::rgb(0x008200)), 2);
+        assert_eq!(nearest_basic(Color::rgb(0x0000ff)), 4);
+    }
+
+    #[test]
+    fn extended_colors_use_the_cube_and_the_gray_ramp() {
+        assert_eq!(nearest_extended(Color::rgb(0xff0000)), 196);
+        assert_eq!(nearest_extended(Color::rgb(0x006699)), 24);
+        assert_eq!(nearest_extended(Color::rgb(0x000000)), 16);
+        assert_eq!(nearest_extended(Color::rgb(0x808080)), 244);
+        assert_eq!(nearest_extended(Color::rgb(0x5c5c5c)), 59);
+        assert_eq!(nearest_extended(Color::rgb(0x767676)), 243);
+    }
+
+    #[test]
+    fn modes_select_the_palette() {
+        let stream = TokenStream::from_tokens(vec![Token::new(TokenKind::Keyword, "fn")]);
+        let options = Options {
+            line_numbers: false,
+            ..Options::default()
+        };
+        let render = |mode| render(&stream, &CLASSIC, &options, mode);
+
+        assert_eq!(render(ColorMode::Basic), "\x1b[1;36mfn\x1b[0m\n");
+        assert_eq!(render(ColorMode::Extended), "\x1b[1;38;5;24mfn\x1b[0m\n");
+        assert_eq!(
+            render(ColorMode::TrueColor),
+            "\x1b[1;38;2;0;102;153mfn\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn control_characters_are_replaced() {
+        assert_eq!(escape("a\x1b[2Jb\tc"), "a\u{fffd}[2Jb\tc");
+    }
+
+    #[test]
+    fn modes_are_parsed() {
+        for mode in [ColorMode::Basic, ColorMode::Extended, ColorMode::TrueColor] {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+        }
+        assert!("8".parse::<ColorMode>().is_err());
+    }
+}
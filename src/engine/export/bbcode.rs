@@ -0,0 +1,127 @@
+//! BBCode for forums. `[code]` blocks of most forums ignore colors, so the
+//! code is set in a monospace font with a `[color]` tag per token instead.
+
+use super::{File, Options};
+use crate::engine::annotations;
+use crate::engine::theme::{Style, Theme};
+use crate::engine::token::TokenStream;
+
+/// Forums collapse runs of spaces, but not of non-breaking ones.
+const SPACE: char = '\u{a0}';
+const TAB_WIDTH: usize = 4;
+
+/// Renders `stream` using the colors of `theme`.
+pub fn render(stream: &TokenStream, theme: &Theme, options: &Options) -> String {
+    format!("[font=monospace]{}[/font]", body(stream, theme, options))
+}
+
+/// Renders several files one after the other, each below a bold file name.
+pub fn render_files(files: &[File], theme: &Theme) -> String {
+    let name = Style {
+        bold: true,
+        ..theme.text
+    };
+
+    files
+        .iter()
+        .map(|file| {
+            format!(
+                "{}\n[font=monospace]{}[/font]",
+                run(&name, file.name),
+                body(file.stream, theme, &file.options)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn body(stream: &TokenStream, theme: &Theme, options: &Options) -> String {
+    let gutter_width = options.gutter_width(stream.lines.len());
+    let mut lines = Vec::with_capacity(stream.lines.len());
+
+    for (index, line) in stream.lines.iter().enumerate() {
+        let emphasis = options.marks.get(index + 1);
+        let mut bbcode = String::new();
+
+        if options.line_numbers {
+            let line_number = Style {
+                color: theme.line_number,
+                ..Style::plain(0)
+            };
+            bbcode.push_str(&run(
+                &line_number,
+                &format!(
+                    "{number:>gutter_width$} ",
+                    number = options.first_line + index
+                ),
+            ));
+        }
+
+        for token in &line.tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+
+            bbcode.push_str(&run(
+                &theme.emphasized_style(token.kind, emphasis),
+                &token.text,
+            ));
+        }
+
+        let callouts = options.annotations.numbers_on(index + 1);
+        if !callouts.is_empty() {
+            bbcode.push_str(&run(
+                &theme.callout,
+                &format!(" {}", annotations::markers(&callouts)),
+            ));
+        }
+
+        lines.push(bbcode);
+    }
+
+    if !options.annotations.is_empty() {
+        lines.push(String::new());
+    }
+    for (number, annotation) in options.annotations.numbered() {
+        lines.push(format!(
+            "{} {}",
+            run(&theme.callout, &annotations::marker(number)),
+            run(&theme.text, &annotation.note)
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// `text` in `style`.
+pub fn run(style: &Style, text: &str) -> String {
+    let mut bbcode = format!("[color={}]{}[/color]", style.color.to_hex(), escape(text));
+
+    if style.italic {
+        bbcode = format!("[i]{bbcode}[/i]");
+    }
+    if style.bold {
+        bbcode = format!("[b]{bbcode}[/b]");
+    }
+    bbcode
+}
+
+/// Keeps `text` from being read as tags and its indentation from collapsing.
+///
+/// BBCode has no escape character. An empty tag after every `[` keeps it from
+/// starting a tag, as no tag name follows it directly.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '[' => escaped.push_str("[[b][/b]"),
+            ' ' => escaped.push(SPACE),
+            '\t' => escaped.extend(std::iter::repeat_n(SPACE, TAB_WIDTH)),
+            '\n' | '\r' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
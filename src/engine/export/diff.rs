@@ -0,0 +1,184 @@
+//! Side-by-side exports of a [`Diff`], as an HTML table or an RTF table.
+
+use super::{html, rtf, Options};
+use crate::engine::diff::{Change, Diff, Segment};
+use crate::engine::theme::{Color, Theme};
+
+/// Width of the line number and code cells in twips.
+const RTF_GUTTER_WIDTH: usize = 600;
+const RTF_CODE_WIDTH: usize = 4200;
+
+fn backgrounds(theme: &Theme, change: Change) -> (Color, Color) {
+    match change {
+        Change::Equal => (theme.background, theme.background),
+        Change::Removed => (theme.removed_background, theme.background),
+        Change::Added => (theme.background, theme.added_background),
+        Change::Changed => (theme.removed_background, theme.added_background),
+    }
+}
+
+pub fn html_table(diff: &Diff, theme: &Theme, options: &Options) -> String {
+    let mut html = format!(
+        "<table style=\"border-collapse: collapse; font-family: {}; font-size: 10pt; color: {};\">",
+        html::FONT_FAMILY,
+        theme.text.color.to_hex()
+    );
+
+    for row in &diff.rows {
+        let (before, after) = diff.segments(row);
+        let (before_background, after_background) = backgrounds(theme, row.change);
+
+        html.push_str("<tr>");
+        html_side(
+            &mut html,
+            theme,
+            options,
+            row.before,
+            &before,
+            before_background,
+            theme.removed_word_background,
+        );
+        html_side(
+            &mut html,
+            theme,
+            options,
+            row.after,
+            &after,
+            after_background,
+            theme.added_word_background,
+        );
+        html.push_str("</tr>");
+    }
+
+    html.push_str("</table>");
+    html
+}
+
+fn html_side(
+    html: &mut String,
+    theme: &Theme,
+    options: &Options,
+    line: Option<usize>,
+    segments: &[Segment],
+    background: Color,
+    word_background: Color,
+) {
+    if options.line_numbers {
+        html.push_str(&format!(
+            "<td style=\"padding: 0 6px; text-align: right; color: {}; background-color: {};\">{}</td>",
+            theme.line_number.to_hex(),
+            background.to_hex(),
+            line.map(|line| (line + options.first_line).to_string()).unwrap_or_default()
+        ));
+    }
+
+    html.push_str(&format!(
+        "<td style=\"padding: 0 6px; white-space: pre; background-color: {};\">",
+        background.to_hex()
+    ));
+    for segment in segments {
+        let mut css = html::css(&theme.style(segment.kind));
+        if segment.marked {
+            css.push_str(&format!(" background-color: {};", word_background.to_hex()));
+        }
+        html.push_str(&format!(
+            "<span style=\"{}\">{}</span>",
+            css,
+            html::escape(segment.text)
+        ));
+    }
+    html.push_str("</td>");
+}
+
+pub fn rtf_table(diff: &Diff, theme: &Theme, options: &Options) -> String {
+    let mut colors = rtf::ColorTable::new(theme);
+    for color in [
+        theme.background,
+        theme.added_background,
+        theme.removed_background,
+        theme.added_word_background,
+        theme.removed_word_background,
+    ] {
+        colors.add(color);
+    }
+
+    let mut document = rtf::header(&colors);
+
+    for row in &diff.rows {
+        let (before, after) = diff.segments(row);
+        let (before_background, after_background) = backgrounds(theme, row.change);
+
+        let mut position = 0;
+        document.push_str("\\trowd\\trgaph60");
+        for background in [before_background, after_background] {
+            if options.line_numbers {
+                position += RTF_GUTTER_WIDTH;
+                document.push_str(&format!(
+                    "\\clcbpat{}\\cellx{}",
+                    colors.index(background),
+                    position
+                ));
+            }
+            position += RTF_CODE_WIDTH;
+            document.push_str(&format!(
+                "\\clcbpat{}\\cellx{}",
+                colors.index(background),
+                position
+            ));
+        }
+        document.push('\n');
+
+        rtf_side(
+            &mut document,
+            theme,
+            options,
+            &colors,
+            row.before,
+            &before,
+            theme.removed_word_background,
+        );
+        rtf_side(
+            &mut document,
+            theme,
+            options,
+            &colors,
+            row.after,
+            &after,
+            theme.added_word_background,
+        );
+        document.push_str("\\row\n");
+    }
+
+    document.push_str("\\pard\n}");
+    document
+}
+
+fn rtf_side(
+    document: &mut String,
+    theme: &Theme,
+    options: &Options,
+    colors: &rtf::ColorTable,
+    line: Option<usize>,
+    segments: &[Segment],
+    word_background: Color,
+) {
+    if options.line_numbers {
+        document.push_str(&format!(
+            "\\pard\\intbl\\qr{{\\cf{} {}}}\\cell ",
+            colors.index(theme.line_number),
+            line.map(|line| (line + options.first_line).to_string())
+                .unwrap_or_default()
+        ));
+    }
+
+    document.push_str("\\pard\\intbl ");
+    for segment in segments {
+        document.push_str(&rtf::run(
+            colors,
+            &theme.style(segment.kind),
+            segment.text,
+            segment.marked.then_some(word_background),
+        ));
+    }
+    document.push_str("\\cell ");
+}
@@ -0,0 +1,208 @@
+//! Word documents: a minimal Office Open XML package with one paragraph per
+//! line and a colored run per token.
+
+use super::zip::ZipWriter;
+use super::{escape_xml, File, Options};
+use crate::engine::annotations;
+use crate::engine::theme::{Color, Style, Theme};
+use crate::engine::token::TokenStream;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/><Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/></Types>"#;
+
+const RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#;
+
+const DOCUMENT_RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#;
+
+/// Name of the paragraph style of code lines.
+const CODE_STYLE: &str = "Code";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// Draws the code in a shaded box with a border.
+    pub boxed: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout { boxed: true }
+    }
+}
+
+/// Renders `stream` as a `.docx` file using the colors of `theme`.
+pub fn render(stream: &TokenStream, theme: &Theme, options: &Options, layout: &Layout) -> Vec<u8> {
+    package(&body(stream, theme, options, layout))
+}
+
+/// Renders several files into one document, each below a bold file name.
+pub fn render_files(files: &[File], theme: &Theme, layout: &Layout) -> Vec<u8> {
+    let mut xml = String::new();
+
+    for file in files {
+        let name = Style {
+            bold: true,
+            ..theme.text
+        };
+        xml.push_str(&format!(
+            "<w:p><w:pPr><w:keepNext/><w:spacing w:before=\"240\" w:after=\"60\"/></w:pPr>{}</w:p>",
+            run(&name, file.name)
+        ));
+        xml.push_str(&body(file.stream, theme, &file.options, layout));
+    }
+
+    package(&xml)
+}
+
+fn package(body: &str) -> Vec<u8> {
+    let document = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:body>{body}<w:sectPr/></w:body></w:document>"
+    );
+
+    let mut zip = ZipWriter::new();
+    zip.add("[Content_Types].xml", CONTENT_TYPES.as_bytes());
+    zip.add("_rels/.rels", RELATIONSHIPS.as_bytes());
+    zip.add(
+        "word/_rels/document.xml.rels",
+        DOCUMENT_RELATIONSHIPS.as_bytes(),
+    );
+    zip.add("word/styles.xml", styles().as_bytes());
+    zip.add("word/document.xml", document.as_bytes());
+    zip.finish()
+}
+
+/// A `Code` paragraph style in Consolas at 10pt without spacing between lines.
+fn styles() -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:styles xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:style w:type=\"paragraph\" w:default=\"1\" w:styleId=\"Normal\"><w:name w:val=\"Normal\"/></w:style><w:style w:type=\"paragraph\" w:customStyle=\"1\" w:styleId=\"{CODE_STYLE}\"><w:name w:val=\"{CODE_STYLE}\"/><w:basedOn w:val=\"Normal\"/><w:pPr><w:spacing w:before=\"0\" w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr><w:rPr><w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/><w:sz w:val=\"20\"/></w:rPr></w:style></w:styles>"
+    )
+}
+
+fn hex(color: Color) -> String {
+    color.to_hex()[1..].to_uppercase()
+}
+
+fn shading(color: Color) -> String {
+    format!(
+        "<w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"{}\"/>",
+        hex(color)
+    )
+}
+
+/// A run of `text` in `style`, tabs becoming tab characters of Word.
+pub fn run(style: &Style, text: &str) -> String {
+    let mut xml = String::from("<w:r><w:rPr>");
+
+    if style.bold {
+        xml.push_str("<w:b/>");
+    }
+    if style.italic {
+        xml.push_str("<w:i/>");
+    }
+    xml.push_str(&format!(
+        "<w:color w:val=\"{}\"/></w:rPr>",
+        hex(style.color)
+    ));
+
+    for (index, part) in text.split('\t').enumerate() {
+        if index > 0 {
+            xml.push_str("<w:tab/>");
+        }
+        if !part.is_empty() {
+            xml.push_str(&format!(
+                "<w:t xml:space=\"preserve\">{}</w:t>",
+                escape_xml(part)
+            ));
+        }
+    }
+
+    xml.push_str("</w:r>");
+    xml
+}
+
+/// The paragraphs of every line and the legend.
+fn body(stream: &TokenStream, theme: &Theme, options: &Options, layout: &Layout) -> String {
+    let mut xml = String::new();
+    let gutter_width = options.gutter_width(stream.lines.len());
+    // Consecutive paragraphs with the same border are drawn as one box.
+    let border = |side: &str| {
+        format!(
+            "<w:{side} w:val=\"single\" w:sz=\"4\" w:space=\"4\" w:color=\"{}\"/>",
+            hex(theme.line_number)
+        )
+    };
+    let box_properties = format!(
+        "<w:pBdr>{}{}{}{}</w:pBdr>",
+        border("top"),
+        border("left"),
+        border("bottom"),
+        border("right")
+    );
+    let box_background = theme.background.mix(theme.text.color, 0.04);
+
+    for (index, line) in stream.lines.iter().enumerate() {
+        let emphasis = options.marks.get(index + 1);
+        let background = emphasis.and_then(|emphasis| theme.emphasis_background(emphasis));
+
+        xml.push_str(&format!("<w:p><w:pPr><w:pStyle w:val=\"{CODE_STYLE}\"/>"));
+        if index + 1 < stream.lines.len() {
+            xml.push_str("<w:keepNext/>");
+        }
+        if layout.boxed {
+            xml.push_str(&box_properties);
+        }
+        match (background, layout.boxed) {
+            (Some(background), _) => xml.push_str(&shading(background)),
+            (None, true) => xml.push_str(&shading(box_background)),
+            (None, false) => {}
+        }
+        xml.push_str("</w:pPr>");
+
+        if options.line_numbers {
+            let line_number = Style {
+                color: theme.line_number,
+                ..Style::plain(0)
+            };
+            xml.push_str(&run(
+                &line_number,
+                &format!(
+                    "{number:>gutter_width$} ",
+                    number = options.first_line + index
+                ),
+            ));
+        }
+
+        for token in &line.tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+
+            let style = theme.emphasized_style(token.kind, emphasis);
+            xml.push_str(&run(&style, &token.text));
+        }
+
+        let callouts = options.annotations.numbers_on(index + 1);
+        if !callouts.is_empty() {
+            xml.push_str(&run(
+                &theme.callout,
+                &format!(" {}", annotations::markers(&callouts)),
+            ));
+        }
+
+        xml.push_str("</w:p>");
+    }
+
+    for (index, (number, annotation)) in options.annotations.numbered().enumerate() {
+        xml.push_str(if index == 0 {
+            "<w:p><w:pPr><w:spacing w:before=\"120\" w:after=\"0\"/></w:pPr>"
+        } else {
+            "<w:p><w:pPr><w:spacing w:before=\"0\" w:after=\"0\"/></w:pPr>"
+        });
+        xml.push_str(&run(&theme.callout, &annotations::marker(number)));
+        xml.push_str(&run(&theme.text, &format!(" {}", annotation.note)));
+        xml.push_str("</w:p>");
+    }
+
+    xml
+}
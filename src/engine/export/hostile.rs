@@ -0,0 +1,391 @@
+//! Input which ends or starts markup in one of the formats, and the checks
+//! that none of it did. Shared by the tests of every renderer, the app's and
+//! the element's included.
+
+use super::ExportFormat;
+
+/// The elements of HTML exports.
+const HTML: [&str; 3] = ["pre", "span", "div"];
+/// The control words of RTF documents and their tables.
+const RTF: [&str; 43] = [
+    "rtf",
+    "ansi",
+    "ansicpg",
+    "deff",
+    "fonttbl",
+    "f",
+    "fmodern",
+    "colortbl",
+    "red",
+    "green",
+    "blue",
+    "pard",
+    "sb",
+    "par",
+    "fs",
+    "cf",
+    "b",
+    "i",
+    "highlight",
+    "cb",
+    "tab",
+    "u",
+    "uc",
+    "line",
+    "shading",
+    "cbpat",
+    "box",
+    "brdrs",
+    "brdrw",
+    "brsp",
+    "brdrcf",
+    "li",
+    "ri",
+    "sa",
+    "ql",
+    "trowd",
+    "trgaph",
+    "clcbpat",
+    "cellx",
+    "intbl",
+    "qr",
+    "cell",
+    "row",
+];
+
+/// Pieces of input which end or start markup in one of the formats.
+const FRAGMENTS: [&str; 40] = [
+    "</pre>",
+    "<script>alert(1)</script>",
+    "<img src=x onerror=alert(1)>",
+    "\"'><x-injected>",
+    "&lt;",
+    "&#x3C;script&#x3E;",
+    "<![CDATA[",
+    "]]>",
+    "<!-- -->",
+    "<?xml version=\"1.0\"?>",
+    "}{\\rtf1\\object ",
+    "{\\field{\\*\\fldinst HYPERLINK \"x\"}}",
+    "\\par ",
+    "\\end{Verbatim}\\input{/etc/passwd}",
+    "\\immediate\\write18{rm -rf}",
+    "$x$ % # ~ ^ _ &",
+    "[url=javascript:alert(1)]x[/url]",
+    "[/font][/color][img]x[/img]",
+    "{html}<b>{html}",
+    "{{",
+    "}}",
+    "[x|y] !x.png! \\\\",
+    "\u{1b}[2J\u{1b}]0;title\u{7}",
+    "\u{9b}31m",
+    "\0\u{b}\u{c}\u{7f}",
+    "\t",
+    "\n",
+    "\r\n",
+    "\r\r",
+    "  ",
+    "\u{2028}\u{202e}",
+    "é𝄞",
+    "def ",
+    "/*",
+    "*/",
+    "//",
+    "\"",
+    "'",
+    "<?php ",
+    "?>",
+];
+
+/// Builds hostile input, seeded so every run tries the same.
+pub struct Random(u64);
+
+impl Default for Random {
+    fn default() -> Self {
+        Random(0x9e37_79b9_7f4a_7c15)
+    }
+}
+
+impl Random {
+    /// xorshift, a number below `bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+
+    /// A few fragments in a row.
+    pub fn hostile(&mut self) -> String {
+        (0..1 + self.below(12))
+            .map(|_| match self.below(FRAGMENTS.len() + 1) {
+                // Lone surrogates can't be in a `String`, JS strings
+                // holding them arrive replaced.
+                0 => String::from_utf16_lossy(&[0xd800, 0x61, 0xdc00]),
+                index => FRAGMENTS[index - 1].to_string(),
+            })
+            .collect()
+    }
+}
+
+/// The name of the element or control word starting at `text`.
+fn name(text: &str, extra: &[char]) -> String {
+    text.chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || extra.contains(c))
+        .collect()
+}
+
+/// Every element is `known` and every `&` starts an escape. XML can't hold
+/// control characters either.
+fn markup(text: &str, known: impl Fn(&str) -> bool, xml: bool) -> Result<(), String> {
+    for (index, _) in text.match_indices('<') {
+        let element = name(
+            text[index + 1..].trim_start_matches('/'),
+            &[':', '?', '!', '-', '_'],
+        );
+        if !known(&element) {
+            return Err(format!("element `{element}`"));
+        }
+    }
+    for (index, _) in text.match_indices('&') {
+        let escapes = ["&amp;", "&lt;", "&gt;", "&quot;", "&#39;"];
+        if !escapes
+            .iter()
+            .any(|escape| text[index..].starts_with(escape))
+        {
+            return Err("unescaped `&`".to_string());
+        }
+    }
+    if let Some(c) = text
+        .chars()
+        .find(|c| xml && c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+    {
+        return Err(format!("control character {c:?}"));
+    }
+
+    Ok(())
+}
+
+/// Braces balance, the document doesn't end early and the only control
+/// words are in `words`.
+fn groups(text: &str, words: &[&str]) -> Result<(), String> {
+    let mut depth = 0usize;
+    let mut chars = text.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                let word = name(&text[index + 1..], &[]);
+                if word.chars().next().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    let word = word.trim_end_matches(|c: char| c.is_ascii_digit());
+                    if !words.contains(&word) {
+                        return Err(format!("control word `{word}`"));
+                    }
+                }
+                chars.next();
+            }
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.checked_sub(1).ok_or("unbalanced `}`")?;
+                if depth == 0 && !text[index + 1..].trim().is_empty() && text.starts_with('{') {
+                    return Err("text after the document".to_string());
+                }
+            }
+            c if c.is_control() && !matches!(c, '\t' | '\n') => {
+                return Err(format!("control character {c:?}"));
+            }
+            _ => {}
+        }
+    }
+
+    match depth {
+        0 => Ok(()),
+        _ => Err("unbalanced `{`".to_string()),
+    }
+}
+
+/// Every tag is one of `tags`, an escaped `[` being followed by another.
+fn bbcode(text: &str) -> Result<(), String> {
+    for (index, _) in text.match_indices('[') {
+        let rest = &text[index + 1..];
+        if rest.starts_with('[') {
+            continue;
+        }
+
+        let tag = name(rest.trim_start_matches('/'), &[]);
+        if !["font", "color", "b", "i"].contains(&tag.as_str()) {
+            return Err(format!("tag `{tag}`"));
+        }
+    }
+
+    Ok(())
+}
+
+/// The only macros are `{panel}` and `{color}`, and links, tables and
+/// images are escaped.
+fn wiki(text: &str) -> Result<(), String> {
+    let mut chars = text.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '{' if !text[index..].starts_with("{{") && !text[..index].ends_with('{') => {
+                let name = name(&text[index + 1..], &[]);
+                if name != "panel" && name != "color" {
+                    return Err(format!("macro `{name}`"));
+                }
+            }
+            '[' | ']' | '|' | '!' => return Err(format!("unescaped `{c}`")),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Escape sequences only set colors and nothing else controls the terminal.
+fn ansi(text: &str) -> Result<(), String> {
+    for (index, c) in text.char_indices() {
+        if c == '\u{1b}' {
+            let sequence = &text[index + 1..];
+            let parameters = sequence
+                .strip_prefix('[')
+                .map(|rest| rest.trim_start_matches(|c: char| c.is_ascii_digit() || c == ';'));
+            if !parameters.is_some_and(|rest| rest.starts_with('m')) {
+                return Err("escape sequence".to_string());
+            }
+        } else if c.is_control() && !matches!(c, '\t' | '\n') {
+            return Err(format!("control character {c:?}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// The stored entries of a zip archive.
+fn entries(zip: &[u8]) -> Vec<(String, &[u8])> {
+    let mut entries = Vec::new();
+    let mut at = 0;
+
+    while zip[at..].starts_with(b"PK\x03\x04") {
+        let field = |offset: usize, size: usize| {
+            zip[at + offset..at + offset + size]
+                .iter()
+                .rev()
+                .fold(0, |value, byte| value << 8 | *byte as usize)
+        };
+        let (size, name) = (field(18, 4), field(26, 2));
+        let start = at + 30 + name + field(28, 2);
+
+        entries.push((
+            String::from_utf8_lossy(&zip[at + 30..at + 30 + name]).into_owned(),
+            &zip[start..start + size],
+        ));
+        at = start + size;
+    }
+
+    entries
+}
+
+/// Whether `output` of `format` is made of its own markup only.
+pub fn check(format: ExportFormat, output: &[u8]) -> Result<(), String> {
+    const SVG: [&str; 8] = [
+        "svg",
+        "defs",
+        "filter",
+        "feDropShadow",
+        "rect",
+        "circle",
+        "text",
+        "tspan",
+    ];
+
+    // Every element of OpenDocument and Office Open XML has a namespace,
+    // apart from the package parts of the latter.
+    let office = |element: &str| {
+        element.contains(':')
+            || [
+                "?xml",
+                "Types",
+                "Default",
+                "Override",
+                "Relationships",
+                "Relationship",
+            ]
+            .contains(&element)
+    };
+
+    let text = String::from_utf8_lossy(output);
+    match format {
+        ExportFormat::Html => markup(&text, |element| HTML.contains(&element), false),
+        ExportFormat::Svg => markup(&text, |element| SVG.contains(&element), true),
+        ExportFormat::Fodt => markup(&text, office, true),
+        ExportFormat::Docx | ExportFormat::Odt => entries(output)
+            .into_iter()
+            .filter(|(name, _)| name.ends_with(".xml") || name.ends_with(".rels"))
+            .try_for_each(|(name, content)| {
+                markup(&String::from_utf8_lossy(content), office, true)
+                    .map_err(|error| format!("{error} in {name}"))
+            }),
+        ExportFormat::Rtf => groups(&text, &RTF),
+        ExportFormat::Latex => groups(
+            &text,
+            &[
+                "usepackage",
+                "definecolor",
+                "providecommand",
+                "csbs",
+                "csob",
+                "cscb",
+                "cshl",
+                "cscallout",
+                "char",
+                "setlength",
+                "fboxsep",
+                "colorbox",
+                "strut",
+                "textbf",
+                "textit",
+                "textcolor",
+                "begin",
+                "end",
+                "noindent",
+                "medskip",
+                "par",
+                "textbackslash",
+                "textasciitilde",
+                "textasciicircum",
+            ],
+        )
+        .and_then(|()| {
+            match text.matches("\\begin{").count() == text.matches("\\end{").count() {
+                true => Ok(()),
+                false => Err("an environment ends early".to_string()),
+            }
+        }),
+        ExportFormat::BbCode => bbcode(&text),
+        ExportFormat::Wiki => wiki(&text),
+        ExportFormat::Ansi => ansi(&text),
+    }
+}
+
+/// Whether `html` is made of the elements of HTML exports and `extra` only.
+pub fn html(text: &str, extra: &[&str]) -> Result<(), String> {
+    markup(
+        text,
+        |element| HTML.contains(&element) || extra.contains(&element),
+        false,
+    )
+}
+
+/// Whether `output`, a side-by-side table of a diff in `format`, is made of
+/// its own markup only.
+pub fn diff_table(format: ExportFormat, output: &str) -> Result<(), String> {
+    match format {
+        ExportFormat::Html => html(output, &["table", "tr", "td"]),
+        ExportFormat::Rtf => groups(output, &RTF),
+        _ => Err(format!("{} has no diff table", format.name())),
+    }
+}
@@ -0,0 +1,153 @@
+use super::{File, Options};
+use crate::engine::annotations;
+use crate::engine::theme::{Style, Theme};
+use crate::engine::token::{Line, TokenStream};
+
+pub const FONT_FAMILY: &str = "Consolas, 'Courier New', Courier, monospace";
+
+/// Renders `stream` as a `<pre>` with inline styles, so the colors survive
+/// being pasted into word processors.
+pub fn render(stream: &TokenStream, theme: &Theme, options: &Options) -> String {
+    let gutter_width = options.gutter_width(stream.lines.len());
+    let mut html = format!(
+        "<pre style=\"font-family: {FONT_FAMILY}; font-size: 10pt; margin: 0; background-color: {background}; color: {color};\">",
+        background = theme.background.to_hex(),
+        color = theme.text.color.to_hex(),
+    );
+
+    for (index, line) in stream.lines.iter().enumerate() {
+        if index > 0 {
+            html.push('\n');
+        }
+        html.push_str(&self::line(line, index, theme, options, gutter_width));
+    }
+
+    html.push_str("</pre>");
+    html.push_str(&legend(theme, options));
+    html
+}
+
+/// The `index`th line of a stream with its background, line number and
+/// callouts, without a line break.
+pub fn line(
+    line: &Line,
+    index: usize,
+    theme: &Theme,
+    options: &Options,
+    gutter_width: usize,
+) -> String {
+    let mut html = String::new();
+
+    let emphasis = options.marks.get(index + 1);
+    let background = emphasis.and_then(|emphasis| theme.emphasis_background(emphasis));
+    if let Some(background) = background {
+        html.push_str(&format!(
+            "<span style=\"display: inline-block; min-width: 100%; background-color: {};\">",
+            background.to_hex()
+        ));
+    }
+
+    if options.line_numbers {
+        html.push_str(&format!(
+            "<span style=\"color: {color};\">{number:>gutter_width$} </span>",
+            color = theme.line_number.to_hex(),
+            number = options.first_line + index,
+        ));
+    }
+
+    for token in &line.tokens {
+        let style = theme.emphasized_style(token.kind, emphasis);
+
+        if style == theme.text {
+            html.push_str(&escape(&token.text));
+        } else {
+            html.push_str(&format!(
+                "<span style=\"{}\">{}</span>",
+                css(&style),
+                escape(&token.text)
+            ));
+        }
+    }
+
+    let callouts = options.annotations.numbers_on(index + 1);
+    if !callouts.is_empty() {
+        html.push_str(&format!(
+            " <span style=\"{}\">{}</span>",
+            css(&theme.callout),
+            annotations::markers(&callouts)
+        ));
+    }
+
+    if background.is_some() {
+        html.push_str("</span>");
+    }
+
+    html
+}
+
+/// The notes of every callout below the code, empty without annotations.
+pub fn legend(theme: &Theme, options: &Options) -> String {
+    if options.annotations.is_empty() {
+        return String::new();
+    }
+
+    let mut html = format!(
+        "<div style=\"font-family: {FONT_FAMILY}; font-size: 10pt; margin-top: 6px; color: {};\">",
+        theme.text.color.to_hex()
+    );
+    for (number, annotation) in options.annotations.numbered() {
+        html.push_str(&format!(
+            "<div><span style=\"{}\">{}</span> {}</div>",
+            css(&theme.callout),
+            annotations::marker(number),
+            escape(&annotation.note)
+        ));
+    }
+    html.push_str("</div>");
+    html
+}
+
+/// Renders several files one after the other, each below its file name.
+pub fn render_files(files: &[File], theme: &Theme) -> String {
+    files
+        .iter()
+        .map(|file| {
+            format!(
+                "<div style=\"font-family: {FONT_FAMILY}; font-size: 10pt; font-weight: bold; margin: 12px 0 4px; color: {};\">{}</div>{}",
+                theme.text.color.to_hex(),
+                escape(file.name),
+                render(file.stream, theme, &file.options)
+            )
+        })
+        .collect()
+}
+
+pub fn css(style: &Style) -> String {
+    let mut css = format!("color: {};", style.color.to_hex());
+
+    if style.bold {
+        css.push_str(" font-weight: bold;");
+    }
+    if style.italic {
+        css.push_str(" font-style: italic;");
+    }
+
+    css
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,200 @@
+//! A LaTeX fragment for papers, to paste into a document loading `xcolor`
+//! and `fancyvrb`. The colors it uses are defined at its top.
+
+use std::collections::BTreeSet;
+
+use super::{File, Options};
+use crate::engine::theme::{Color, Style, Theme};
+use crate::engine::token::TokenStream;
+
+/// Macros for the characters the `Verbatim` environment treats as commands,
+/// and for highlighted lines and callouts.
+const MACROS: &str = r"\providecommand{\csbs}{\char`\\}
+\providecommand{\csob}{\char`\{}
+\providecommand{\cscb}{\char`\}}
+\providecommand{\cshl}[2]{{\setlength{\fboxsep}{0pt}\colorbox{#1}{\strut#2}}}
+\providecommand{\cscallout}[1]{\textbf{(#1)}}
+";
+
+/// Renders `stream` as a `Verbatim` environment using the colors of `theme`.
+pub fn render(stream: &TokenStream, theme: &Theme, options: &Options) -> String {
+    let mut colors = BTreeSet::new();
+    let body = body(&mut colors, stream, theme, options);
+
+    header(&colors) + &body
+}
+
+/// Renders several files into one fragment, each below a bold file name.
+pub fn render_files(files: &[File], theme: &Theme) -> String {
+    let mut colors = BTreeSet::new();
+    let mut body = String::new();
+
+    for (index, file) in files.iter().enumerate() {
+        if index > 0 {
+            body.push_str("\\medskip\n");
+        }
+        body.push_str(&format!(
+            "\\noindent\\textbf{{{}}}\n",
+            escape_text(file.name)
+        ));
+        body.push_str(&self::body(&mut colors, file.stream, theme, &file.options));
+    }
+
+    header(&colors) + &body
+}
+
+/// The color definitions and macros the body relies on.
+fn header(colors: &BTreeSet<String>) -> String {
+    let mut latex = String::from("% Needs \\usepackage{xcolor} and \\usepackage{fancyvrb}.\n");
+
+    for hex in colors {
+        latex.push_str(&format!(
+            "\\definecolor{{cs{hex}}}{{HTML}}{{{}}}\n",
+            hex.to_uppercase()
+        ));
+    }
+    latex.push_str(MACROS);
+    latex
+}
+
+/// Name of `color` in the fragment, noting it down to be defined.
+fn color(colors: &mut BTreeSet<String>, color: Color) -> String {
+    let hex = color.to_hex()[1..].to_string();
+    let name = format!("cs{hex}");
+
+    colors.insert(hex);
+    name
+}
+
+/// `text` in `style`, `text` being escaped already.
+fn run(colors: &mut BTreeSet<String>, style: &Style, text: &str) -> String {
+    let mut latex = format!("\\textcolor{{{}}}{{{text}}}", color(colors, style.color));
+
+    if style.italic {
+        latex = format!("\\textit{{{latex}}}");
+    }
+    if style.bold {
+        latex = format!("\\textbf{{{latex}}}");
+    }
+    latex
+}
+
+/// The environment with every line, followed by the legend.
+fn body(
+    colors: &mut BTreeSet<String>,
+    stream: &TokenStream,
+    theme: &Theme,
+    options: &Options,
+) -> String {
+    let mut latex = String::from("\\begin{Verbatim}[commandchars=\\\\\\{\\}]\n");
+    let gutter_width = options.gutter_width(stream.lines.len());
+
+    for (index, line) in stream.lines.iter().enumerate() {
+        let emphasis = options.marks.get(index + 1);
+        let mut content = String::new();
+
+        if options.line_numbers {
+            content.push_str(&run(
+                colors,
+                &Style {
+                    color: theme.line_number,
+                    ..Style::plain(0)
+                },
+                &format!(
+                    "{number:>gutter_width$} ",
+                    number = options.first_line + index
+                ),
+            ));
+        }
+
+        for token in &line.tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+
+            let style = theme.emphasized_style(token.kind, emphasis);
+            content.push_str(&run(colors, &style, &escape(&token.text)));
+        }
+
+        let callouts = options.annotations.numbers_on(index + 1);
+        if !callouts.is_empty() {
+            let markers = callouts
+                .iter()
+                .map(|number| format!("\\cscallout{{{number}}}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            content.push_str(&run(colors, &theme.callout, &format!(" {markers}")));
+        }
+
+        match emphasis.and_then(|emphasis| theme.emphasis_background(emphasis)) {
+            Some(background) => latex.push_str(&format!(
+                "\\cshl{{{}}}{{{content}}}\n",
+                color(colors, background)
+            )),
+            None => {
+                latex.push_str(&content);
+                latex.push('\n');
+            }
+        }
+    }
+
+    latex.push_str("\\end{Verbatim}\n");
+    latex.push_str(&legend(colors, theme, options));
+    latex
+}
+
+/// The notes of every callout as paragraphs below the code.
+fn legend(colors: &mut BTreeSet<String>, theme: &Theme, options: &Options) -> String {
+    let mut latex = String::new();
+
+    for (number, annotation) in options.annotations.numbered() {
+        latex.push_str(&format!(
+            "\\noindent{} {}\\par\n",
+            run(colors, &theme.callout, &format!("\\cscallout{{{number}}}")),
+            run(colors, &theme.text, &escape_text(&annotation.note))
+        ));
+    }
+
+    latex
+}
+
+/// Escapes `text` for a `Verbatim` environment with `\`, `{` and `}` as
+/// command characters, where every other character stands for itself.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\csbs{}"),
+            '{' => escaped.push_str("\\csob{}"),
+            '}' => escaped.push_str("\\cscb{}"),
+            // TeX rejects most control characters and ends lines at others.
+            c if c.is_control() && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Escapes `text` for running text outside of the environment.
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            c if c.is_control() && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,306 @@
+//! Renders a [`TokenStream`](super::token::TokenStream) into formats that keep
+//! their colors when pasted into documents.
+
+pub mod ansi;
+pub mod bbcode;
+pub mod diff;
+pub mod docx;
+#[cfg(any(test, feature = "testing"))]
+#[doc(hidden)]
+pub mod hostile;
+pub mod html;
+pub mod latex;
+pub mod odt;
+pub mod print;
+pub mod rtf;
+pub mod scene;
+pub mod svg;
+pub mod wiki;
+pub mod zip;
+
+use std::str::FromStr;
+
+use super::annotations::Annotations;
+use super::marks::LineMarks;
+use super::theme::Theme;
+use super::token::TokenStream;
+
+/// Settings shared by every exporter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub line_numbers: bool,
+    pub first_line: usize,
+    pub marks: LineMarks,
+    pub annotations: Annotations,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            line_numbers: true,
+            first_line: 1,
+            marks: LineMarks::default(),
+            annotations: Annotations::default(),
+        }
+    }
+}
+
+impl Options {
+    /// Width in characters of the widest line number in a document of `lines` lines.
+    pub fn gutter_width(&self, lines: usize) -> usize {
+        (self.first_line + lines.max(1) - 1).to_string().len()
+    }
+}
+
+/// One of several files exported into a single document.
+pub struct File<'a> {
+    pub name: &'a str,
+    pub stream: &'a TokenStream,
+    pub options: Options,
+}
+
+/// The plain text of every file, each below its name.
+pub fn text(files: &[File]) -> String {
+    files
+        .iter()
+        .map(|file| format!("{}\n\n{}", file.name, file.stream.text()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Escapes `text` for XML documents, dropping the control characters XML
+/// can't contain at all.
+pub fn escape_xml(text: &str) -> String {
+    let allowed = text
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>();
+
+    html::escape(&allowed)
+}
+
+/// File formats the preview can be downloaded as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Html,
+    Rtf,
+    Docx,
+    Odt,
+    Fodt,
+    Latex,
+    BbCode,
+    Wiki,
+    Ansi,
+    Svg,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 10] = [
+        ExportFormat::Html,
+        ExportFormat::Rtf,
+        ExportFormat::Docx,
+        ExportFormat::Odt,
+        ExportFormat::Fodt,
+        ExportFormat::Latex,
+        ExportFormat::BbCode,
+        ExportFormat::Wiki,
+        ExportFormat::Ansi,
+        ExportFormat::Svg,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "HTML",
+            ExportFormat::Rtf => "RTF",
+            ExportFormat::Docx => "Word document",
+            ExportFormat::Odt => "OpenDocument text",
+            ExportFormat::Fodt => "Flat OpenDocument XML",
+            ExportFormat::Latex => "LaTeX",
+            ExportFormat::BbCode => "BBCode",
+            ExportFormat::Wiki => "Confluence / Jira wiki markup",
+            ExportFormat::Ansi => "ANSI terminal text",
+            ExportFormat::Svg => "SVG image",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Rtf => "rtf",
+            ExportFormat::Docx => "docx",
+            ExportFormat::Odt => "odt",
+            ExportFormat::Fodt => "fodt",
+            ExportFormat::Latex => "tex",
+            ExportFormat::BbCode => "bbcode",
+            ExportFormat::Wiki => "wiki",
+            ExportFormat::Ansi => "ans",
+            ExportFormat::Svg => "svg",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "text/html",
+            ExportFormat::Rtf => "application/rtf",
+            ExportFormat::Docx => {
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            }
+            ExportFormat::Odt => "application/vnd.oasis.opendocument.text",
+            ExportFormat::Fodt => "application/vnd.oasis.opendocument.text-flat-xml",
+            ExportFormat::Latex => "application/x-tex",
+            ExportFormat::BbCode | ExportFormat::Wiki | ExportFormat::Ansi => "text/plain",
+            ExportFormat::Svg => "image/svg+xml",
+        }
+    }
+
+    /// Whether the format can show a diff in compare mode, as a table.
+    pub fn supports_diff(self) -> bool {
+        matches!(self, ExportFormat::Html | ExportFormat::Rtf)
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    /// Parses the extension of a format, e.g. `rtf` or `tex`.
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        let format = format.to_ascii_lowercase();
+
+        ExportFormat::ALL
+            .into_iter()
+            .find(|known| known.extension() == format)
+            .ok_or_else(|| {
+                let known = ExportFormat::ALL.map(ExportFormat::extension);
+                format!(
+                    "unknown format `{format}`, expected one of {}",
+                    known.join(", ")
+                )
+            })
+    }
+}
+
+/// Settings of single formats, which the others ignore.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Settings {
+    pub docx: docx::Layout,
+    pub image: scene::Layout,
+    pub colors: ansi::ColorMode,
+}
+
+/// Renders `files` in `format`, `None` without any file. Images show the
+/// first file only.
+pub fn render(
+    format: ExportFormat,
+    files: &[File],
+    theme: &Theme,
+    settings: &Settings,
+) -> Option<Vec<u8>> {
+    let rendered = match (format, files) {
+        (_, []) => return None,
+        (ExportFormat::Docx, [file]) => {
+            docx::render(file.stream, theme, &file.options, &settings.docx)
+        }
+        (ExportFormat::Docx, files) => docx::render_files(files, theme, &settings.docx),
+        (ExportFormat::Odt | ExportFormat::Fodt, files) => {
+            let package = if format == ExportFormat::Odt {
+                odt::Package::Zipped
+            } else {
+                odt::Package::Flat
+            };
+
+            match files {
+                [file] => odt::render(file.stream, theme, &file.options, package),
+                files => odt::render_files(files, theme, package),
+            }
+        }
+        (ExportFormat::Html, [file]) => html::render(file.stream, theme, &file.options).into(),
+        (ExportFormat::Html, files) => html::render_files(files, theme).into(),
+        (ExportFormat::Rtf, [file]) => rtf::render(file.stream, theme, &file.options).into(),
+        (ExportFormat::Rtf, files) => rtf::render_files(files, theme).into(),
+        (ExportFormat::Latex, [file]) => latex::render(file.stream, theme, &file.options).into(),
+        (ExportFormat::Latex, files) => latex::render_files(files, theme).into(),
+        (ExportFormat::BbCode, [file]) => bbcode::render(file.stream, theme, &file.options).into(),
+        (ExportFormat::BbCode, files) => bbcode::render_files(files, theme).into(),
+        (ExportFormat::Wiki, [file]) => wiki::render(file.stream, theme, &file.options).into(),
+        (ExportFormat::Wiki, files) => wiki::render_files(files, theme).into(),
+        (ExportFormat::Ansi, [file]) => {
+            ansi::render(file.stream, theme, &file.options, settings.colors).into()
+        }
+        (ExportFormat::Ansi, files) => ansi::render_files(files, theme, settings.colors).into(),
+        (ExportFormat::Svg, [file, ..]) => {
+            svg::render(file.stream, theme, &file.options, &settings.image).into()
+        }
+    };
+
+    Some(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hostile::{self, Random};
+    use super::*;
+    use crate::engine::diff::Diff;
+    use crate::engine::marks::Emphasis;
+    use crate::engine::theme::CLASSIC;
+    use crate::engine::{brushes, highlight};
+
+    #[test]
+    fn hostile_input_never_becomes_markup() {
+        let mut random = Random::default();
+        let brushes = brushes::all();
+
+        for _ in 0..200 {
+            let code = random.hostile();
+            let name = random.hostile();
+            let note = random.hostile();
+            let brush = &brushes[random.below(brushes.len())];
+            let stream = highlight(&code, brush);
+            let changed = highlight(&random.hostile(), brush);
+
+            let mut options = Options::default();
+            options.marks.toggle(1, Emphasis::ALL[random.below(4)]);
+            options.annotations.add(1, &note);
+            let files = [
+                File {
+                    name: &name,
+                    stream: &stream,
+                    options: options.clone(),
+                },
+                File {
+                    name: &note,
+                    stream: &stream,
+                    options,
+                },
+            ];
+
+            for format in ExportFormat::ALL {
+                for files in [&files[..1], &files[..]] {
+                    let output = render(format, files, &CLASSIC, &Settings::default()).unwrap();
+                    if let Err(error) = hostile::check(format, &output) {
+                        panic!(
+                            "{} of {code:?} named {name:?} noted {note:?} with {}: {error}",
+                            format.name(),
+                            brush.aliases[0]
+                        );
+                    }
+                }
+            }
+
+            let diff = Diff::new(stream.clone(), changed);
+            for (format, output) in [
+                (
+                    ExportFormat::Html,
+                    diff::html_table(&diff, &CLASSIC, &files[0].options),
+                ),
+                (
+                    ExportFormat::Rtf,
+                    diff::rtf_table(&diff, &CLASSIC, &files[0].options),
+                ),
+            ] {
+                if let Err(error) = hostile::diff_table(format, &output) {
+                    panic!("{} diff of {code:?}: {error}", format.name());
+                }
+            }
+        }
+    }
+}
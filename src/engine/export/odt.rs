@@ -0,0 +1,258 @@
+//! OpenDocument text for LibreOffice Writer, either as an `.odt` package or
+//! as a single flat `.fodt` XML file. Every style a token uses becomes an
+//! automatic text style.
+
+use super::zip::ZipWriter;
+use super::{escape_xml, File, Options};
+use crate::engine::annotations;
+use crate::engine::theme::{Color, Style, Theme};
+use crate::engine::token::TokenStream;
+
+const MIME_TYPE: &str = "application/vnd.oasis.opendocument.text";
+const VERSION: &str = "1.3";
+const NAMESPACES: &str = concat!(
+    "xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\" ",
+    "xmlns:style=\"urn:oasis:names:tc:opendocument:xmlns:style:1.0\" ",
+    "xmlns:text=\"urn:oasis:names:tc:opendocument:xmlns:text:1.0\" ",
+    "xmlns:fo=\"urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0\" ",
+    "xmlns:svg=\"urn:oasis:names:tc:opendocument:xmlns:svg-compatible:1.0\"",
+);
+const FONT_FACES: &str = "<office:font-face-decls><style:font-face style:name=\"Consolas\" svg:font-family=\"Consolas\" style:font-family-generic=\"modern\" style:font-pitch=\"fixed\"/></office:font-face-decls>";
+/// The paragraph style of code lines.
+const STYLES: &str = "<office:styles><style:style style:name=\"Code\" style:family=\"paragraph\"><style:paragraph-properties fo:margin-top=\"0cm\" fo:margin-bottom=\"0cm\"/><style:text-properties style:font-name=\"Consolas\" fo:font-size=\"10pt\"/></style:style></office:styles>";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Package {
+    /// A zip archive, `.odt`.
+    Zipped,
+    /// A single XML file, `.fodt`.
+    Flat,
+}
+
+/// The automatic styles of a document, named after their index.
+#[derive(Default)]
+struct AutomaticStyles {
+    text: Vec<Style>,
+    /// Backgrounds of marked lines, each a paragraph style based on `Code`.
+    paragraphs: Vec<Color>,
+}
+
+impl AutomaticStyles {
+    fn text(&mut self, style: Style) -> String {
+        let index = match self.text.iter().position(|known| *known == style) {
+            Some(index) => index,
+            None => {
+                self.text.push(style);
+                self.text.len() - 1
+            }
+        };
+
+        format!("T{}", index + 1)
+    }
+
+    fn paragraph(&mut self, background: Color) -> String {
+        let index = match self
+            .paragraphs
+            .iter()
+            .position(|known| *known == background)
+        {
+            Some(index) => index,
+            None => {
+                self.paragraphs.push(background);
+                self.paragraphs.len() - 1
+            }
+        };
+
+        format!("P{}", index + 1)
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = String::from("<office:automatic-styles>");
+
+        for (index, style) in self.text.iter().enumerate() {
+            xml.push_str(&format!(
+                "<style:style style:name=\"T{}\" style:family=\"text\"><style:text-properties fo:color=\"{}\"",
+                index + 1,
+                style.color.to_hex()
+            ));
+            if style.bold {
+                xml.push_str(" fo:font-weight=\"bold\"");
+            }
+            if style.italic {
+                xml.push_str(" fo:font-style=\"italic\"");
+            }
+            xml.push_str("/></style:style>");
+        }
+        for (index, background) in self.paragraphs.iter().enumerate() {
+            xml.push_str(&format!(
+                "<style:style style:name=\"P{}\" style:family=\"paragraph\" style:parent-style-name=\"Code\"><style:paragraph-properties fo:background-color=\"{}\"/></style:style>",
+                index + 1,
+                background.to_hex()
+            ));
+        }
+
+        xml.push_str("</office:automatic-styles>");
+        xml
+    }
+}
+
+/// Renders `stream` using the colors of `theme`.
+pub fn render(stream: &TokenStream, theme: &Theme, options: &Options, package: Package) -> Vec<u8> {
+    let mut styles = AutomaticStyles::default();
+    let body = body(&mut styles, stream, theme, options);
+
+    document(&styles, &body, package)
+}
+
+/// Renders several files into one document, each below a bold file name.
+pub fn render_files(files: &[File], theme: &Theme, package: Package) -> Vec<u8> {
+    let mut styles = AutomaticStyles::default();
+    let mut body = String::new();
+
+    for file in files {
+        let name = Style {
+            bold: true,
+            ..theme.text
+        };
+        body.push_str(&format!(
+            "<text:p>{}</text:p>",
+            span(&mut styles, name, file.name)
+        ));
+        body.push_str(&self::body(&mut styles, file.stream, theme, &file.options));
+    }
+
+    document(&styles, &body, package)
+}
+
+fn document(styles: &AutomaticStyles, body: &str, package: Package) -> Vec<u8> {
+    let header = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+    let automatic_styles = styles.to_xml();
+    let body = format!("<office:body><office:text>{body}</office:text></office:body>");
+
+    match package {
+        Package::Flat => format!(
+            "{header}<office:document {NAMESPACES} office:version=\"{VERSION}\" office:mimetype=\"{MIME_TYPE}\">{FONT_FACES}{STYLES}{automatic_styles}{body}</office:document>"
+        )
+        .into_bytes(),
+        Package::Zipped => {
+            let manifest = format!(
+                "{header}<manifest:manifest xmlns:manifest=\"urn:oasis:names:tc:opendocument:xmlns:manifest:1.0\" manifest:version=\"{VERSION}\"><manifest:file-entry manifest:full-path=\"/\" manifest:version=\"{VERSION}\" manifest:media-type=\"{MIME_TYPE}\"/><manifest:file-entry manifest:full-path=\"content.xml\" manifest:media-type=\"text/xml\"/><manifest:file-entry manifest:full-path=\"styles.xml\" manifest:media-type=\"text/xml\"/></manifest:manifest>"
+            );
+            let styles = format!(
+                "{header}<office:document-styles {NAMESPACES} office:version=\"{VERSION}\">{FONT_FACES}{STYLES}</office:document-styles>"
+            );
+            let content = format!(
+                "{header}<office:document-content {NAMESPACES} office:version=\"{VERSION}\">{FONT_FACES}{automatic_styles}{body}</office:document-content>"
+            );
+
+            // The mime type has to come first for the file to be recognized.
+            let mut zip = ZipWriter::new();
+            zip.add("mimetype", MIME_TYPE.as_bytes());
+            zip.add("META-INF/manifest.xml", manifest.as_bytes());
+            zip.add("styles.xml", styles.as_bytes());
+            zip.add("content.xml", content.as_bytes());
+            zip.finish()
+        }
+    }
+}
+
+/// Escapes `text` for a paragraph, where runs of spaces and tabs would
+/// otherwise collapse.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let spaces = rest.len() - rest.trim_start_matches(' ').len();
+        if spaces == 1 {
+            escaped.push_str("<text:s/>");
+            rest = &rest[1..];
+        } else if spaces > 1 {
+            escaped.push_str(&format!("<text:s text:c=\"{spaces}\"/>"));
+            rest = &rest[spaces..];
+        } else if let Some(after) = rest.strip_prefix('\t') {
+            escaped.push_str("<text:tab/>");
+            rest = after;
+        } else {
+            let end = rest.find([' ', '\t']).unwrap_or(rest.len());
+            escaped.push_str(&escape_xml(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+
+    escaped
+}
+
+fn span(styles: &mut AutomaticStyles, style: Style, text: &str) -> String {
+    format!(
+        "<text:span text:style-name=\"{}\">{}</text:span>",
+        styles.text(style),
+        escape(text)
+    )
+}
+
+/// The paragraphs of every line and the legend.
+fn body(
+    styles: &mut AutomaticStyles,
+    stream: &TokenStream,
+    theme: &Theme,
+    options: &Options,
+) -> String {
+    let mut xml = String::new();
+    let gutter_width = options.gutter_width(stream.lines.len());
+
+    for (index, line) in stream.lines.iter().enumerate() {
+        let emphasis = options.marks.get(index + 1);
+        let paragraph = match emphasis.and_then(|emphasis| theme.emphasis_background(emphasis)) {
+            Some(background) => styles.paragraph(background),
+            None => "Code".to_string(),
+        };
+
+        xml.push_str(&format!("<text:p text:style-name=\"{paragraph}\">"));
+
+        if options.line_numbers {
+            let line_number = Style {
+                color: theme.line_number,
+                ..Style::plain(0)
+            };
+            xml.push_str(&span(
+                styles,
+                line_number,
+                &format!(
+                    "{number:>gutter_width$} ",
+                    number = options.first_line + index
+                ),
+            ));
+        }
+
+        for token in &line.tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+
+            let style = theme.emphasized_style(token.kind, emphasis);
+            xml.push_str(&span(styles, style, &token.text));
+        }
+
+        let callouts = options.annotations.numbers_on(index + 1);
+        if !callouts.is_empty() {
+            xml.push_str(&span(
+                styles,
+                theme.callout,
+                &format!(" {}", annotations::markers(&callouts)),
+            ));
+        }
+
+        xml.push_str("</text:p>");
+    }
+
+    for (number, annotation) in options.annotations.numbered() {
+        xml.push_str(&format!(
+            "<text:p>{}{}</text:p>",
+            span(styles, theme.callout, &annotations::marker(number)),
+            span(styles, theme.text, &format!(" {}", annotation.note))
+        ));
+    }
+
+    xml
+}
@@ -0,0 +1,183 @@
+//! Pages for printing. Browsers break pages wherever the paper ends, so the
+//! pages are cut here instead: short files stay on one page, long ones break
+//! after a blank line where possible, and line numbers go on counting.
+
+use super::File;
+
+/// Files of one line would be read as part of the next page otherwise.
+const MIN_LINES: usize = 3;
+/// How far back from the end of a page a blank line is looked for.
+const LOOKBACK: usize = 8;
+/// The name above a file and the gap below it.
+const FILE_NAME_LINES: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// Lines of 10pt code which fit a page of A4 or Letter below a header.
+    pub lines_per_page: usize,
+    /// Puts the file names and the page number above every page.
+    pub headers: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            lines_per_page: 56,
+            headers: true,
+        }
+    }
+}
+
+/// The lines `start..end` of the `file`th file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Part {
+    pub file: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits the lines of every file into pages. `files` holds the code lines and
+/// the legend lines of each file.
+fn paginate(files: &[(&[bool], usize)], named: bool, layout: &Layout) -> Vec<Vec<Part>> {
+    let page = layout.lines_per_page.max(MIN_LINES * 2);
+    let name = if named { FILE_NAME_LINES } else { 0 };
+    let mut pages = vec![Vec::new()];
+    let mut used = 0;
+
+    for (file, &(blank, legend)) in files.iter().enumerate() {
+        let lines = blank.len();
+        let whole = name + lines + legend;
+
+        // A file which fits a page is never split, a longer one at least
+        // starts with a few lines.
+        let free = page.saturating_sub(used);
+        if used > 0 && ((whole > free && whole <= page) || free < name + MIN_LINES) {
+            pages.push(Vec::new());
+            used = 0;
+        }
+        used += name;
+
+        let mut start = 0;
+        loop {
+            let room = page.saturating_sub(used).max(MIN_LINES);
+            let mut end = (start + room).min(lines);
+
+            if end < lines {
+                // Breaks after the last blank line near the end of the page and
+                // leaves no lonely lines for the next one.
+                end = (end.saturating_sub(LOOKBACK).max(start + MIN_LINES)..end)
+                    .rev()
+                    .find(|&line| blank[line - 1])
+                    .unwrap_or(end)
+                    .min(lines.saturating_sub(MIN_LINES).max(start + MIN_LINES));
+            }
+
+            pages.last_mut().unwrap().push(Part { file, start, end });
+            used += end - start;
+
+            if end >= lines {
+                break;
+            }
+            pages.push(Vec::new());
+            used = 0;
+            start = end;
+        }
+
+        used += legend;
+    }
+
+    pages
+}
+
+/// The parts of `files` on every page. Several files are printed below
+/// their names.
+pub fn pages(files: &[File], layout: &Layout) -> Vec<Vec<Part>> {
+    let lines = files
+        .iter()
+        .map(|file| {
+            let blank = file
+                .stream
+                .lines
+                .iter()
+                .map(|line| line.text().trim().is_empty())
+                .collect::<Vec<_>>();
+            let legend = match file.options.annotations.numbered().count() {
+                0 => 0,
+                notes => notes + 1,
+            };
+
+            (blank, legend)
+        })
+        .collect::<Vec<_>>();
+
+    paginate(
+        &lines
+            .iter()
+            .map(|(blank, legend)| (blank.as_slice(), *legend))
+            .collect::<Vec<_>>(),
+        files.len() > 1,
+        layout,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(lines_per_page: usize) -> Layout {
+        Layout {
+            lines_per_page,
+            headers: true,
+        }
+    }
+
+    fn ranges(pages: &[Vec<Part>]) -> Vec<Vec<(usize, usize, usize)>> {
+        pages
+            .iter()
+            .map(|parts| {
+                parts
+                    .iter()
+                    .map(|part| (part.file, part.start, part.end))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn short_files_move_to_the_next_page() {
+        let first = vec![false; 15];
+        let second = vec![false; 10];
+        let pages = paginate(&[(&first, 0), (&second, 0)], false, &layout(20));
+
+        assert_eq!(ranges(&pages), vec![vec![(0, 0, 15)], vec![(1, 0, 10)]]);
+    }
+
+    #[test]
+    fn long_files_break_after_a_blank_line() {
+        let mut lines = vec![false; 50];
+        lines[16] = true;
+        let pages = paginate(&[(&lines, 0)], false, &layout(20));
+
+        assert_eq!(
+            ranges(&pages),
+            vec![vec![(0, 0, 17)], vec![(0, 17, 37)], vec![(0, 37, 50)]]
+        );
+    }
+
+    #[test]
+    fn no_page_starts_with_a_lonely_line() {
+        let lines = vec![false; 21];
+        let pages = paginate(&[(&lines, 0)], false, &layout(20));
+
+        assert_eq!(ranges(&pages), vec![vec![(0, 0, 18)], vec![(0, 18, 21)]]);
+    }
+
+    #[test]
+    fn legends_take_up_room() {
+        let first = vec![false; 10];
+        let second = vec![false; 5];
+        let pages = paginate(&[(&first, 6), (&second, 0)], false, &layout(20));
+
+        assert_eq!(ranges(&pages), vec![vec![(0, 0, 10)], vec![(1, 0, 5)]]);
+    }
+}
@@ -0,0 +1,222 @@
+use super::{File, Options};
+use crate::engine::annotations;
+use crate::engine::marks::Emphasis;
+use crate::engine::theme::{Color, Style, Theme};
+use crate::engine::token::TokenStream;
+
+/// Renders `stream` as an RTF document using the colors of `theme`.
+pub fn render(stream: &TokenStream, theme: &Theme, options: &Options) -> String {
+    let colors = ColorTable::new(theme);
+    let mut rtf = header(&colors);
+
+    rtf.push_str(&body(&colors, stream, theme, options));
+    rtf.push('}');
+    rtf
+}
+
+/// Renders several files into one document, each below a bold file name.
+pub fn render_files(files: &[File], theme: &Theme) -> String {
+    let colors = ColorTable::new(theme);
+    let mut rtf = header(&colors);
+
+    for (index, file) in files.iter().enumerate() {
+        rtf.push_str(if index == 0 {
+            "\\pard "
+        } else {
+            "\\pard\\sb240 "
+        });
+        rtf.push_str(&run(
+            &colors,
+            &Style {
+                bold: true,
+                ..theme.text
+            },
+            file.name,
+            None,
+        ));
+        rtf.push_str("\\par\n");
+        rtf.push_str(&body(&colors, file.stream, theme, &file.options));
+    }
+
+    rtf.push('}');
+    rtf
+}
+
+/// The paragraphs of every line and the legend, without the document around them.
+fn body(colors: &ColorTable, stream: &TokenStream, theme: &Theme, options: &Options) -> String {
+    let mut rtf = String::new();
+    let gutter_width = options.gutter_width(stream.lines.len());
+
+    for (index, line) in stream.lines.iter().enumerate() {
+        let emphasis = options.marks.get(index + 1);
+
+        rtf.push_str("\\pard");
+        if let Some(background) = emphasis.and_then(|emphasis| theme.emphasis_background(emphasis))
+        {
+            rtf.push_str(&format!("\\cbpat{}", colors.index(background)));
+        }
+        rtf.push(' ');
+
+        if options.line_numbers {
+            rtf.push_str(&format!(
+                "{{\\cf{} {}}}",
+                colors.index(theme.line_number),
+                escape(&format!(
+                    "{number:>gutter_width$} ",
+                    number = options.first_line + index
+                ))
+            ));
+        }
+
+        for token in &line.tokens {
+            let style = theme.emphasized_style(token.kind, emphasis);
+
+            rtf.push_str(&run(colors, &style, &token.text, None));
+        }
+
+        let callouts = options.annotations.numbers_on(index + 1);
+        if !callouts.is_empty() {
+            rtf.push_str(&run(
+                colors,
+                &theme.callout,
+                &format!(" {}", annotations::markers(&callouts)),
+                None,
+            ));
+        }
+
+        rtf.push_str("\\par\n");
+    }
+
+    rtf.push_str(&legend(colors, theme, options));
+    rtf
+}
+
+/// The notes of every callout as paragraphs below the code.
+fn legend(colors: &ColorTable, theme: &Theme, options: &Options) -> String {
+    let mut rtf = String::new();
+
+    for (index, (number, annotation)) in options.annotations.numbered().enumerate() {
+        rtf.push_str(if index == 0 {
+            "\\pard\\sb120 "
+        } else {
+            "\\pard "
+        });
+        rtf.push_str(&run(
+            colors,
+            &theme.callout,
+            &annotations::marker(number),
+            None,
+        ));
+        rtf.push_str(&run(
+            colors,
+            &theme.text,
+            &format!(" {}", annotation.note),
+            None,
+        ));
+        rtf.push_str("\\par\n");
+    }
+
+    rtf
+}
+
+/// The colors of a document, referenced by their index in the `\colortbl`.
+pub struct ColorTable {
+    colors: Vec<Color>,
+}
+
+impl ColorTable {
+    pub fn new(theme: &Theme) -> Self {
+        let mut colors = ColorTable { colors: Vec::new() };
+
+        colors.add(theme.text.color);
+        colors.add(theme.line_number);
+        colors.add(theme.callout.color);
+        for (_, style) in theme.styles {
+            colors.add(style.color);
+        }
+        for style in theme
+            .styles
+            .iter()
+            .map(|(_, style)| style)
+            .chain([&theme.text])
+        {
+            colors.add(theme.dim(*style).color);
+        }
+        for emphasis in Emphasis::ALL {
+            if let Some(background) = theme.emphasis_background(emphasis) {
+                colors.add(background);
+            }
+        }
+
+        colors
+    }
+
+    pub fn add(&mut self, color: Color) {
+        if !self.colors.contains(&color) {
+            self.colors.push(color);
+        }
+    }
+
+    /// Index into the color table, which is 1 based as entry 0 is the default color.
+    pub fn index(&self, color: Color) -> usize {
+        self.colors.iter().position(|c| *c == color).unwrap_or(0) + 1
+    }
+}
+
+/// Opens the document, everything after it has to be closed with a `}`.
+pub fn header(colors: &ColorTable) -> String {
+    let mut rtf =
+        String::from("{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern Consolas;}}{\\colortbl ;");
+    for color in &colors.colors {
+        rtf.push_str(&format!(
+            "\\red{}\\green{}\\blue{};",
+            color.r, color.g, color.b
+        ));
+    }
+    rtf.push_str("}\n\\f0\\fs20 ");
+    rtf
+}
+
+/// A group of `text` in `style`, optionally on a highlighted background.
+pub fn run(colors: &ColorTable, style: &Style, text: &str, background: Option<Color>) -> String {
+    let mut rtf = format!("{{\\cf{}", colors.index(style.color));
+
+    if style.bold {
+        rtf.push_str("\\b");
+    }
+    if style.italic {
+        rtf.push_str("\\i");
+    }
+    if let Some(background) = background {
+        rtf.push_str(&format!("\\highlight{}", colors.index(background)));
+    }
+
+    rtf.push(' ');
+    rtf.push_str(&escape(text));
+    rtf.push('}');
+    rtf
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '{' => escaped.push_str("\\{"),
+            '}' => escaped.push_str("\\}"),
+            '\t' => escaped.push_str("\\tab "),
+            // Readers skip line breaks, other controls could confuse them.
+            c if c.is_control() => {}
+            c if c.is_ascii() => escaped.push(c),
+            c => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    escaped.push_str(&format!("\\u{}?", *unit as i16));
+                }
+            }
+        }
+    }
+
+    escaped
+}
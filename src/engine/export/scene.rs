@@ -0,0 +1,285 @@
+//! Positions of everything drawn in an image of code. Every run of text is
+//! placed at a column computed from [`Metrics`] instead of relying on the font,
+//! so images look the same whichever renderer draws the scene.
+
+use super::Options;
+use crate::engine::annotations;
+use crate::engine::theme::{Color, Style, Theme};
+use crate::engine::token::TokenStream;
+
+/// Dot colors of the window chrome, as on macOS.
+const CHROME_DOTS: [Color; 3] = [
+    Color::rgb(0xff5f56),
+    Color::rgb(0xffbd2e),
+    Color::rgb(0x27c93f),
+];
+const CHROME_HEIGHT: f64 = 28.0;
+const CHROME_DOT_RADIUS: f64 = 6.0;
+const CHROME_DOT_SPACING: f64 = 20.0;
+const CORNER_RADIUS: f64 = 8.0;
+/// Transparent space around the card for its shadow to fall on.
+const SHADOW_MARGIN: f64 = 32.0;
+pub const SHADOW_BLUR: f64 = 24.0;
+pub const SHADOW_OFFSET: f64 = 8.0;
+pub const SHADOW_COLOR: &str = "rgba(0, 0, 0, 0.35)";
+
+/// Sizes of a monospace font in px.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Metrics {
+    pub font_size: f64,
+    /// Advance of a single column.
+    pub char_width: f64,
+    pub line_height: f64,
+}
+
+/// Consolas and most other monospace fonts advance 0.55 em per column.
+pub const MONOSPACE: Metrics = Metrics {
+    font_size: 14.0,
+    char_width: 14.0 * 0.55,
+    line_height: 14.0 * 1.4,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layout {
+    pub metrics: Metrics,
+    /// Space around the code in px.
+    pub padding: f64,
+    /// Draws a title bar with three dots above the code.
+    pub chrome: bool,
+    /// Casts a shadow below the card, which makes the image larger.
+    pub shadow: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            metrics: MONOSPACE,
+            padding: 16.0,
+            chrome: true,
+            shadow: false,
+        }
+    }
+}
+
+/// Columns `c` takes up: two for wide east asian characters, none for
+/// combining marks.
+pub fn columns(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036f | 0x200b..=0x200f | 0xfe00..=0xfe0f => 0,
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3041..=0x33ff
+        | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+/// Columns `text` takes up.
+pub fn width(text: &str) -> usize {
+    text.chars().map(columns).sum()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    pub x: f64,
+    pub text: String,
+    pub style: Style,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radius: f64,
+        color: Color,
+        shadow: bool,
+    },
+    Circle {
+        x: f64,
+        y: f64,
+        radius: f64,
+        color: Color,
+    },
+    /// A line of text, `y` being its baseline.
+    Text { y: f64, runs: Vec<Run> },
+}
+
+/// Everything to draw, back to front.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scene {
+    pub width: f64,
+    pub height: f64,
+    pub font_size: f64,
+    pub shapes: Vec<Shape>,
+}
+
+/// Lays out `stream` as a card with the code, its callouts and their legend.
+pub fn layout(stream: &TokenStream, theme: &Theme, options: &Options, layout: &Layout) -> Scene {
+    let metrics = &layout.metrics;
+    let gutter_width = options.gutter_width(stream.lines.len());
+    let gutter_columns = if options.line_numbers {
+        gutter_width + 1
+    } else {
+        0
+    };
+
+    let legend = options
+        .annotations
+        .numbered()
+        .map(|(number, annotation)| (annotations::marker(number), annotation.note.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut lines = Vec::with_capacity(stream.lines.len());
+    for (index, line) in stream.lines.iter().enumerate() {
+        let callouts = options.annotations.numbers_on(index + 1);
+        let markers = (!callouts.is_empty()).then(|| annotations::markers(&callouts));
+
+        lines.push((line, markers));
+    }
+
+    let columns = lines
+        .iter()
+        .map(|(line, markers)| {
+            gutter_columns
+                + width(&line.text())
+                + markers.as_ref().map_or(0, |markers| 1 + width(markers))
+        })
+        .chain(
+            legend
+                .iter()
+                .map(|(marker, note)| width(marker) + 1 + width(note)),
+        )
+        .max()
+        .unwrap_or(0);
+
+    let margin = if layout.shadow { SHADOW_MARGIN } else { 0.0 };
+    let rows = stream.lines.len()
+        + if legend.is_empty() {
+            0
+        } else {
+            legend.len() + 1
+        };
+    let card_width = 2.0 * layout.padding + columns as f64 * metrics.char_width;
+    let card_height = layout.padding
+        + if layout.chrome { CHROME_HEIGHT } else { 0.0 }
+        + rows as f64 * metrics.line_height
+        + layout.padding;
+    let top = margin + layout.padding + if layout.chrome { CHROME_HEIGHT } else { 0.0 };
+    let baseline = (metrics.line_height - metrics.font_size) / 2.0 + metrics.font_size * 0.8;
+    let left = margin + layout.padding;
+    let x = |column: usize| left + column as f64 * metrics.char_width;
+
+    let mut shapes = vec![Shape::Rect {
+        x: margin,
+        y: margin,
+        width: card_width,
+        height: card_height,
+        radius: CORNER_RADIUS,
+        color: theme.background,
+        shadow: layout.shadow,
+    }];
+
+    if layout.chrome {
+        for (index, color) in CHROME_DOTS.iter().enumerate() {
+            shapes.push(Shape::Circle {
+                x: left + CHROME_DOT_RADIUS + index as f64 * CHROME_DOT_SPACING,
+                y: margin + CHROME_HEIGHT / 2.0 + layout.padding / 2.0,
+                radius: CHROME_DOT_RADIUS,
+                color: *color,
+            });
+        }
+    }
+
+    for (index, (line, markers)) in lines.iter().enumerate() {
+        let y = top + index as f64 * metrics.line_height;
+        let emphasis = options.marks.get(index + 1);
+
+        if let Some(background) = emphasis.and_then(|emphasis| theme.emphasis_background(emphasis))
+        {
+            shapes.push(Shape::Rect {
+                x: margin,
+                y,
+                width: card_width,
+                height: metrics.line_height,
+                radius: 0.0,
+                color: background,
+                shadow: false,
+            });
+        }
+
+        let mut runs = Vec::with_capacity(line.tokens.len() + 2);
+        if options.line_numbers {
+            runs.push(Run {
+                x: x(0),
+                text: format!("{:>gutter_width$}", options.first_line + index),
+                style: Style {
+                    color: theme.line_number,
+                    ..Style::plain(0)
+                },
+            });
+        }
+
+        let mut column = gutter_columns;
+        for token in &line.tokens {
+            runs.push(Run {
+                x: x(column),
+                text: token.text.clone(),
+                style: theme.emphasized_style(token.kind, emphasis),
+            });
+            column += width(&token.text);
+        }
+
+        if let Some(markers) = markers {
+            runs.push(Run {
+                x: x(column + 1),
+                text: markers.clone(),
+                style: theme.callout,
+            });
+        }
+
+        shapes.push(Shape::Text {
+            y: y + baseline,
+            runs,
+        });
+    }
+
+    for (index, (marker, note)) in legend.iter().enumerate() {
+        let y = top + (stream.lines.len() + 1 + index) as f64 * metrics.line_height;
+
+        shapes.push(Shape::Text {
+            y: y + baseline,
+            runs: vec![
+                Run {
+                    x: x(0),
+                    text: marker.clone(),
+                    style: theme.callout,
+                },
+                Run {
+                    x: x(width(marker) + 1),
+                    text: note.to_string(),
+                    style: theme.text,
+                },
+            ],
+        });
+    }
+
+    Scene {
+        width: card_width + 2.0 * margin,
+        height: card_height + 2.0 * margin,
+        font_size: metrics.font_size,
+        shapes,
+    }
+}
@@ -0,0 +1,236 @@
+//! Vector images of code for slides, drawn from a [`scene`].
+
+use super::html::FONT_FAMILY;
+use super::scene::{self, Layout, Shape, SHADOW_BLUR, SHADOW_COLOR, SHADOW_OFFSET};
+use super::{escape_xml, Options};
+use crate::engine::theme::{Style, Theme};
+use crate::engine::token::TokenStream;
+
+/// Formats a coordinate with at most two decimals.
+fn number(value: f64) -> String {
+    let formatted = format!("{value:.2}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn attributes(style: &Style) -> String {
+    let mut attributes = format!(" fill=\"{}\"", style.color.to_hex());
+
+    if style.bold {
+        attributes.push_str(" font-weight=\"bold\"");
+    }
+    if style.italic {
+        attributes.push_str(" font-style=\"italic\"");
+    }
+
+    attributes
+}
+
+/// Renders `stream` as a standalone SVG document.
+pub fn render(stream: &TokenStream, theme: &Theme, options: &Options, layout: &Layout) -> String {
+    let scene = scene::layout(stream, theme, options, layout);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\" font-family=\"{FONT_FAMILY}\" font-size=\"{font_size}\">",
+        width = number(scene.width),
+        height = number(scene.height),
+        font_size = number(scene.font_size),
+    );
+    if layout.shadow {
+        svg.push_str(&format!(
+            "<defs><filter id=\"shadow\" x=\"-50%\" y=\"-50%\" width=\"200%\" height=\"200%\"><feDropShadow dx=\"0\" dy=\"{}\" stdDeviation=\"{}\" flood-color=\"{SHADOW_COLOR}\"/></filter></defs>",
+            number(SHADOW_OFFSET),
+            number(SHADOW_BLUR / 2.0)
+        ));
+    }
+
+    for shape in &scene.shapes {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                radius,
+                color,
+                shadow,
+            } => {
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+                    number(*x),
+                    number(*y),
+                    number(*width),
+                    number(*height)
+                ));
+                if *radius > 0.0 {
+                    svg.push_str(&format!(" rx=\"{}\"", number(*radius)));
+                }
+                if *shadow {
+                    svg.push_str(" filter=\"url(#shadow)\"");
+                }
+                svg.push_str(&format!(" fill=\"{}\"/>", color.to_hex()));
+            }
+            Shape::Circle {
+                x,
+                y,
+                radius,
+                color,
+            } => svg.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+                number(*x),
+                number(*y),
+                number(*radius),
+                color.to_hex()
+            )),
+            Shape::Text { y, runs } => {
+                svg.push_str(&format!(
+                    "<text y=\"{}\" xml:space=\"preserve\">",
+                    number(*y)
+                ));
+                for run in runs {
+                    svg.push_str(&format!(
+                        "<tspan x=\"{}\"{}>{}</tspan>",
+                        number(run.x),
+                        attributes(&run.style),
+                        escape_xml(&run.text)
+                    ));
+                }
+                svg.push_str("</text>");
+            }
+        }
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::export::scene::{width, Metrics};
+    use crate::engine::theme::CLASSIC;
+    use crate::engine::token::{Token, TokenKind};
+
+    fn stream(code: &str) -> TokenStream {
+        TokenStream::from_tokens(vec![Token::new(TokenKind::Plain, code)])
+    }
+
+    fn plain_layout() -> Layout {
+        Layout {
+            metrics: Metrics {
+                font_size: 10.0,
+                char_width: 6.0,
+                line_height: 12.0,
+            },
+            padding: 10.0,
+            chrome: false,
+            shadow: false,
+        }
+    }
+
+    fn no_line_numbers() -> Options {
+        Options {
+            line_numbers: false,
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn size_follows_the_metrics() {
+        let svg = render(
+            &stream("ab\nabcd"),
+            &CLASSIC,
+            &no_line_numbers(),
+            &plain_layout(),
+        );
+
+        // 4 columns of 6px and 2 lines of 12px inside 10px of padding.
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"44\" height=\"44\"")
+        );
+    }
+
+    #[test]
+    fn line_numbers_take_up_the_gutter() {
+        let svg = render(&stream("a"), &CLASSIC, &Options::default(), &plain_layout());
+
+        assert!(svg.contains("<tspan x=\"10\" fill=\"#5c5c5c\">1</tspan>"));
+        assert!(svg.contains("<tspan x=\"22\" fill=\"#000000\">a</tspan>"));
+    }
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(width("漢字a"), 5);
+        assert_eq!(width("e\u{301}"), 1);
+
+        let svg = render(
+            &TokenStream::from_tokens(vec![
+                Token::new(TokenKind::String, "漢"),
+                Token::new(TokenKind::Plain, "a"),
+            ]),
+            &CLASSIC,
+            &no_line_numbers(),
+            &plain_layout(),
+        );
+
+        assert!(svg.contains("<tspan x=\"22\" fill=\"#000000\">a</tspan>"));
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let svg = render(
+            &stream("a < b && c > \"d\""),
+            &CLASSIC,
+            &no_line_numbers(),
+            &plain_layout(),
+        );
+
+        assert!(svg.contains("a &lt; b &amp;&amp; c &gt; &quot;d&quot;"));
+        assert!(!svg.contains("a < b"));
+    }
+
+    #[test]
+    fn chrome_adds_a_title_bar() {
+        let layout = Layout {
+            chrome: true,
+            ..plain_layout()
+        };
+        let with_chrome = render(&stream("a"), &CLASSIC, &no_line_numbers(), &layout);
+        let without_chrome = render(&stream("a"), &CLASSIC, &no_line_numbers(), &plain_layout());
+
+        assert_eq!(with_chrome.matches("<circle").count(), 3);
+        assert_eq!(without_chrome.matches("<circle").count(), 0);
+        assert!(with_chrome.contains("height=\"60\""));
+        assert!(without_chrome.contains("height=\"32\""));
+    }
+
+    #[test]
+    fn shadow_adds_a_margin() {
+        let layout = Layout {
+            shadow: true,
+            ..plain_layout()
+        };
+        let svg = render(&stream("a"), &CLASSIC, &no_line_numbers(), &layout);
+
+        // 32px on every side of the 26px by 32px card.
+        assert!(svg.contains("width=\"90\" height=\"96\""));
+        assert!(svg.contains(
+            "<rect x=\"32\" y=\"32\" width=\"26\" height=\"32\" rx=\"8\" filter=\"url(#shadow)\""
+        ));
+        assert!(svg.contains("<tspan x=\"42\""));
+    }
+
+    #[test]
+    fn output_is_deterministic() {
+        let options = Options::default();
+        let layout = Layout::default();
+        let code = stream("fn main() {\n    println!(\"hi\");\n}");
+
+        assert_eq!(
+            render(&code, &CLASSIC, &options, &layout),
+            render(&code, &CLASSIC, &options, &layout)
+        );
+    }
+}
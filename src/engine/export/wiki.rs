@@ -0,0 +1,129 @@
+//! Wiki markup of Confluence and Jira. `{noformat}` and `{code}` blocks show
+//! macros as text, so the code is set in a `{panel}` with a `{color}` macro
+//! around every monospaced token instead.
+
+use super::{File, Options};
+use crate::engine::annotations;
+use crate::engine::theme::{Style, Theme};
+use crate::engine::token::TokenStream;
+
+/// Wikis collapse runs of spaces, but not of non-breaking ones.
+const SPACE: char = '\u{a0}';
+const TAB_WIDTH: usize = 4;
+
+/// Renders `stream` using the colors of `theme`.
+pub fn render(stream: &TokenStream, theme: &Theme, options: &Options) -> String {
+    panel(stream, theme, options)
+}
+
+/// Renders several files one after the other, each below a bold file name.
+pub fn render_files(files: &[File], theme: &Theme) -> String {
+    files
+        .iter()
+        .map(|file| {
+            format!(
+                "*{}*\n{}",
+                escape(file.name),
+                panel(file.stream, theme, &file.options)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn panel(stream: &TokenStream, theme: &Theme, options: &Options) -> String {
+    let gutter_width = options.gutter_width(stream.lines.len());
+    let mut lines = vec![format!("{{panel:bgColor={}}}", theme.background.to_hex())];
+
+    for (index, line) in stream.lines.iter().enumerate() {
+        let emphasis = options.marks.get(index + 1);
+        let mut wiki = String::new();
+
+        if options.line_numbers {
+            let line_number = Style {
+                color: theme.line_number,
+                ..Style::plain(0)
+            };
+            wiki.push_str(&run(
+                &line_number,
+                &format!(
+                    "{number:>gutter_width$} ",
+                    number = options.first_line + index
+                ),
+            ));
+        }
+
+        for token in &line.tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+
+            wiki.push_str(&run(
+                &theme.emphasized_style(token.kind, emphasis),
+                &token.text,
+            ));
+        }
+
+        let callouts = options.annotations.numbers_on(index + 1);
+        if !callouts.is_empty() {
+            wiki.push_str(&run(
+                &theme.callout,
+                &format!(" {}", annotations::markers(&callouts)),
+            ));
+        }
+
+        // An empty line would end the paragraph and leave a larger gap.
+        if wiki.is_empty() {
+            wiki.push(SPACE);
+        }
+        lines.push(wiki);
+    }
+
+    lines.push("{panel}".to_string());
+
+    for (number, annotation) in options.annotations.numbered() {
+        lines.push(format!(
+            "{} {}",
+            run(&theme.callout, &annotations::marker(number)),
+            escape(&annotation.note)
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// `text` in `style`, set in a monospace font with its indentation kept.
+pub fn run(style: &Style, text: &str) -> String {
+    let spaced = text
+        .replace('\t', &" ".repeat(TAB_WIDTH))
+        .replace(' ', &SPACE.to_string());
+    let mut wiki = format!("{{{{{}}}}}", escape(&spaced));
+
+    if style.italic {
+        wiki = format!("_{wiki}_");
+    }
+    if style.bold {
+        wiki = format!("*{wiki}*");
+    }
+    format!("{{color:{}}}{wiki}{{color}}", style.color.to_hex())
+}
+
+/// Escapes the characters which start markup.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            // A backslash before another one is a line break, not an escape.
+            '\\' => escaped.push_str("&#92;"),
+            '{' | '}' | '[' | ']' | '*' | '_' | '-' | '+' | '^' | '~' | '?' | '|' | '!' | '#' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,150 @@
+//! Just enough of the zip format to package office documents. Entries are
+//! stored without compression and without timestamps, so the same files give
+//! the same archive.
+
+/// 1980-01-01, the earliest date zip can express.
+const DOS_DATE: u16 = (1 << 5) | 1;
+/// Sets the names as UTF-8.
+const UTF8_NAMES: u16 = 1 << 11;
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut index = 0;
+
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+
+    table
+}
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, byte| {
+        CRC_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Collects files into an archive in the order they are added.
+#[derive(Default)]
+pub struct ZipWriter {
+    data: Vec<u8>,
+    entries: Vec<Entry>,
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        ZipWriter::default()
+    }
+
+    pub fn add(&mut self, name: &str, content: &[u8]) {
+        let entry = Entry {
+            name: name.to_string(),
+            crc: crc32(content),
+            size: content.len() as u32,
+            offset: self.data.len() as u32,
+        };
+
+        self.data.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&20u16.to_le_bytes());
+        self.header(&entry);
+        self.data.extend_from_slice(entry.name.as_bytes());
+        self.data.extend_from_slice(content);
+
+        self.entries.push(entry);
+    }
+
+    /// The fields local and central headers share, from the flags up to the
+    /// extra field length.
+    fn header(&mut self, entry: &Entry) {
+        self.data.extend_from_slice(&UTF8_NAMES.to_le_bytes());
+        // Stored, at midnight.
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+        self.data.extend_from_slice(&DOS_DATE.to_le_bytes());
+        self.data.extend_from_slice(&entry.crc.to_le_bytes());
+        self.data.extend_from_slice(&entry.size.to_le_bytes());
+        self.data.extend_from_slice(&entry.size.to_le_bytes());
+        self.data
+            .extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+    }
+
+    /// Appends the central directory and returns the archive.
+    pub fn finish(mut self) -> Vec<u8> {
+        let directory = self.data.len() as u32;
+        let entries = std::mem::take(&mut self.entries);
+
+        for entry in &entries {
+            self.data.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            // Made by and needed to extract: version 2.0.
+            self.data.extend_from_slice(&20u16.to_le_bytes());
+            self.data.extend_from_slice(&20u16.to_le_bytes());
+            self.header(entry);
+            // Comment length, disk, internal and external attributes.
+            self.data.extend_from_slice(&[0; 10]);
+            self.data.extend_from_slice(&entry.offset.to_le_bytes());
+            self.data.extend_from_slice(entry.name.as_bytes());
+        }
+
+        let directory_size = self.data.len() as u32 - directory;
+        self.data.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&[0; 4]);
+        self.data
+            .extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        self.data
+            .extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        self.data.extend_from_slice(&directory_size.to_le_bytes());
+        self.data.extend_from_slice(&directory.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc_matches_the_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn entries_are_stored_in_order() {
+        let mut zip = ZipWriter::new();
+        zip.add("mimetype", b"text/plain");
+        zip.add("a/b.xml", b"<b/>");
+        let archive = zip.finish();
+
+        assert_eq!(&archive[..4], b"PK\x03\x04");
+        assert_eq!(&archive[30..38], b"mimetype");
+        assert_eq!(&archive[38..48], b"text/plain");
+        assert_eq!(archive.windows(4).filter(|w| w == b"PK\x01\x02").count(), 2);
+
+        let end = &archive[archive.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+    }
+}
@@ -0,0 +1,161 @@
+//! The languages the app offers, each backed by the brush named by its
+//! `css_class`.
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::brushes;
+
+#[derive(Deserialize, Serialize, Clone, Copy)]
+pub struct ProgrammingLanguage {
+    pub name: &'static str,
+    pub css_class: &'static str,
+    /// File extensions, without the dot, of files in this language.
+    #[serde(skip)]
+    pub extensions: &'static [&'static str],
+    pub image_file: &'static str,
+    pub image_file_extension: &'static str,
+}
+
+impl ProgrammingLanguage {
+    pub fn by_css_class(css_class: &str) -> Option<Self> {
+        PROGRAMMING_LANGUAGES
+            .iter()
+            .find(|language| language.css_class == css_class)
+            .copied()
+    }
+
+    /// The language of a file named `file_name` according to its extension.
+    pub fn by_extension(file_name: &str) -> Option<Self> {
+        let (_, extension) = file_name.rsplit_once('.')?;
+        let extension = extension.to_lowercase();
+
+        PROGRAMMING_LANGUAGES
+            .iter()
+            .find(|language| language.extensions.contains(&extension.as_str()))
+            .copied()
+    }
+
+    /// The language highlighted by the brush with `alias`, e.g. `py`.
+    pub fn by_alias(alias: &str) -> Option<Self> {
+        let brush = brushes::find(alias)?;
+
+        PROGRAMMING_LANGUAGES
+            .iter()
+            .find(|language| {
+                brushes::find(language.css_class).is_some_and(|known| std::ptr::eq(known, brush))
+            })
+            .copied()
+    }
+
+    /// The language of a file named `file_name`, from its extension or
+    /// failing that from its `code`.
+    pub fn for_file(file_name: &str, code: &str) -> Option<Self> {
+        Self::by_extension(file_name)
+            .or_else(|| super::detect::language(code).and_then(Self::by_css_class))
+    }
+}
+
+/// Every language with its aliases and extensions, as the APIs list them.
+pub fn to_json() -> serde_json::Value {
+    PROGRAMMING_LANGUAGES
+        .iter()
+        .map(|language| {
+            json!({
+                "id": language.css_class,
+                "name": language.name,
+                "aliases": brushes::find(language.css_class).map_or(&[][..], |brush| brush.aliases),
+                "extensions": language.extensions,
+            })
+        })
+        .collect()
+}
+
+pub const PROGRAMMING_LANGUAGES: [ProgrammingLanguage; 12] = [
+    ProgrammingLanguage {
+        name: "C / C++",
+        css_class: "cpp",
+        extensions: &["c", "cc", "cpp", "cxx", "h", "hh", "hpp", "hxx", "ino"],
+        image_file: "images/cpp",
+        image_file_extension: "png",
+    },
+    ProgrammingLanguage {
+        name: "C#",
+        css_class: "csharp",
+        extensions: &["cs", "csx"],
+        image_file: "images/csharp",
+        image_file_extension: "png",
+    },
+    ProgrammingLanguage {
+        name: "Python",
+        css_class: "python",
+        extensions: &["py", "pyw", "pyi"],
+        image_file: "images/python",
+        image_file_extension: "png",
+    },
+    ProgrammingLanguage {
+        name: "CSS",
+        css_class: "css",
+        extensions: &["css"],
+        image_file: "images/css",
+        image_file_extension: "png",
+    },
+    ProgrammingLanguage {
+        name: "Delphi",
+        css_class: "delphi",
+        extensions: &["pas", "pp", "dpr", "dpk", "lpr"],
+        image_file: "images/delphi",
+        image_file_extension: "png",
+    },
+    ProgrammingLanguage {
+        name: "VisualBasic",
+        css_class: "vb",
+        extensions: &["vb", "vbs", "bas", "cls", "frm"],
+        image_file: "images/viauslbasic",
+        image_file_extension: "svg",
+    },
+    ProgrammingLanguage {
+        name: "Java",
+        css_class: "java",
+        extensions: &["java"],
+        image_file: "images/java",
+        image_file_extension: "png",
+    },
+    ProgrammingLanguage {
+        name: "JavaScript",
+        css_class: "js",
+        extensions: &["js", "mjs", "cjs", "jsx", "ts", "tsx", "json"],
+        image_file: "images/javascript",
+        image_file_extension: "png",
+    },
+    ProgrammingLanguage {
+        name: "Ruby",
+        css_class: "ruby",
+        extensions: &["rb", "rake", "gemspec", "ru"],
+        image_file: "images/ruby",
+        image_file_extension: "png",
+    },
+    ProgrammingLanguage {
+        name: "SQL",
+        css_class: "sql",
+        extensions: &["sql"],
+        image_file: "images/sql",
+        image_file_extension: "png",
+    },
+    ProgrammingLanguage {
+        name: "HTML / XML",
+        css_class: "xml",
+        extensions: &[
+            "xml", "html", "htm", "xhtml", "xsl", "xslt", "svg", "xaml", "csproj",
+        ],
+        image_file: "images/html",
+        image_file_extension: "png",
+    },
+    ProgrammingLanguage {
+        name: "PHP",
+        css_class: "php",
+        extensions: &["php", "phtml"],
+        image_file: "images/php",
+        image_file_extension: "png",
+    },
+];
@@ -0,0 +1,341 @@
+//! Emphasis of single lines or ranges, e.g. `3,7-9`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Emphasis {
+    Highlighted,
+    Dimmed,
+    Added,
+    Removed,
+}
+
+impl Emphasis {
+    pub const ALL: [Emphasis; 4] = [
+        Emphasis::Highlighted,
+        Emphasis::Dimmed,
+        Emphasis::Added,
+        Emphasis::Removed,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Emphasis::Highlighted => "Highlight",
+            Emphasis::Dimmed => "Dim",
+            Emphasis::Added => "Added",
+            Emphasis::Removed => "Removed",
+        }
+    }
+
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Emphasis::Highlighted => "highlighted",
+            Emphasis::Dimmed => "dimmed",
+            Emphasis::Added => "added",
+            Emphasis::Removed => "removed",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RangeError {
+    Invalid(String),
+    Reversed(usize, usize),
+    /// A line past the last one, which is the second number.
+    PastEnd(usize, usize),
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeError::Invalid(part) => write!(f, "`{part}` is not a line number or range"),
+            RangeError::Reversed(start, end) => {
+                write!(f, "the range {start}-{end} ends before it starts")
+            }
+            RangeError::PastEnd(line, lines) => {
+                write!(f, "line {line} is past the last line, {lines}")
+            }
+        }
+    }
+}
+
+/// Parses a comma separated list of 1 based line numbers and ranges.
+pub fn parse_ranges(spec: &str) -> Result<Vec<RangeInclusive<usize>>, RangeError> {
+    let line = |part: &str| -> Result<usize, RangeError> {
+        match part.trim().parse::<usize>() {
+            Ok(line) if line > 0 => Ok(line),
+            _ => Err(RangeError::Invalid(part.trim().to_string())),
+        }
+    };
+
+    spec.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (line(start)?, line(end)?);
+                if end < start {
+                    return Err(RangeError::Reversed(start, end));
+                }
+                Ok(start..=end)
+            }
+            None => line(part).map(|line| line..=line),
+        })
+        .collect()
+}
+
+/// The emphasis of every marked line, keyed by 1 based line number.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineMarks {
+    lines: BTreeMap<usize, Emphasis>,
+}
+
+impl LineMarks {
+    pub fn get(&self, line: usize) -> Option<Emphasis> {
+        self.lines.get(&line).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Marks every line in `spec` with `emphasis`, refusing lines past the
+    /// last of the `lines` there are.
+    pub fn apply(
+        &mut self,
+        spec: &str,
+        emphasis: Emphasis,
+        lines: usize,
+    ) -> Result<(), RangeError> {
+        let ranges = Self::ranges_within(spec, lines)?;
+
+        for range in ranges {
+            for line in range {
+                self.lines.insert(line, emphasis);
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks exactly the lines in `spec` with `emphasis`, unmarking the other
+    /// lines which had it. Nothing changes if `spec` is refused.
+    pub fn replace(
+        &mut self,
+        spec: &str,
+        emphasis: Emphasis,
+        lines: usize,
+    ) -> Result<(), RangeError> {
+        let ranges = Self::ranges_within(spec, lines)?;
+
+        self.lines.retain(|_, marked| *marked != emphasis);
+        for range in ranges {
+            for line in range {
+                self.lines.insert(line, emphasis);
+            }
+        }
+        Ok(())
+    }
+
+    fn ranges_within(spec: &str, lines: usize) -> Result<Vec<RangeInclusive<usize>>, RangeError> {
+        let ranges = parse_ranges(spec)?;
+        if let Some(end) = ranges.iter().map(|range| *range.end()).max() {
+            if end > lines {
+                return Err(RangeError::PastEnd(end, lines));
+            }
+        }
+
+        Ok(ranges)
+    }
+
+    /// The marks of `specs`, line ranges by the CSS class of their emphasis,
+    /// e.g. `highlighted` to `3,7-9`, in code of `lines` lines.
+    pub fn from_specs(specs: &BTreeMap<String, String>, lines: usize) -> Result<Self, String> {
+        let mut marks = LineMarks::default();
+        for (name, spec) in specs {
+            let emphasis = Emphasis::ALL
+                .into_iter()
+                .find(|emphasis| emphasis.css_class() == name)
+                .ok_or_else(|| format!("unknown emphasis `{name}`"))?;
+            marks
+                .apply(spec, emphasis, lines)
+                .map_err(|error| format!("invalid {name} lines: {error}"))?;
+        }
+
+        Ok(marks)
+    }
+
+    /// Marks `line` with `emphasis` or removes the mark if it already has it.
+    pub fn toggle(&mut self, line: usize, emphasis: Emphasis) {
+        if self.lines.get(&line) == Some(&emphasis) {
+            self.lines.remove(&line);
+        } else {
+            self.lines.insert(line, emphasis);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// The lines marked with `emphasis` in the notation [`parse_ranges`] reads.
+    pub fn to_spec(&self, emphasis: Emphasis) -> String {
+        let mut ranges: Vec<RangeInclusive<usize>> = Vec::new();
+
+        for (&line, _) in self.lines.iter().filter(|(_, marked)| **marked == emphasis) {
+            match ranges.last_mut() {
+                Some(last) if *last.end() + 1 == line => *last = *last.start()..=line,
+                _ => ranges.push(line..=line),
+            }
+        }
+
+        ranges
+            .iter()
+            .map(|range| {
+                if range.start() == range.end() {
+                    range.start().to_string()
+                } else {
+                    format!("{}-{}", range.start(), range.end())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lines_and_ranges() {
+        assert_eq!(parse_ranges(" 3, 7-9 ,,"), Ok(vec![3..=3, 7..=9]));
+        assert_eq!(parse_ranges("1-3,2-4"), Ok(vec![1..=3, 2..=4]));
+        assert_eq!(parse_ranges(""), Ok(vec![]));
+        assert_eq!(parse_ranges("5-5"), Ok(vec![5..=5]));
+    }
+
+    #[test]
+    fn rejects_malformed_ranges() {
+        assert_eq!(parse_ranges("9-7"), Err(RangeError::Reversed(9, 7)));
+        assert_eq!(parse_ranges("0"), Err(RangeError::Invalid("0".into())));
+        assert_eq!(parse_ranges("a-3"), Err(RangeError::Invalid("a".into())));
+        assert_eq!(
+            parse_ranges("1-2-3"),
+            Err(RangeError::Invalid("2-3".into()))
+        );
+        assert_eq!(parse_ranges("-4"), Err(RangeError::Invalid("".into())));
+        assert_eq!(
+            parse_ranges("1-99999999999999999999"),
+            Err(RangeError::Invalid("99999999999999999999".into()))
+        );
+    }
+
+    #[test]
+    fn applies_ranges_within_the_document() {
+        let mut marks = LineMarks::default();
+        marks.apply("1-3,2-4", Emphasis::Highlighted, 10).unwrap();
+        marks.apply("4", Emphasis::Dimmed, 10).unwrap();
+
+        assert_eq!(marks.get(1), Some(Emphasis::Highlighted));
+        assert_eq!(marks.get(3), Some(Emphasis::Highlighted));
+        assert_eq!(marks.get(4), Some(Emphasis::Dimmed));
+        assert_eq!(marks.get(5), None);
+        assert_eq!(marks.to_spec(Emphasis::Highlighted), "1-3");
+
+        let huge = format!("1-{}", usize::MAX);
+        assert_eq!(
+            marks.apply(&huge, Emphasis::Added, 10),
+            Err(RangeError::PastEnd(usize::MAX, 10))
+        );
+        assert_eq!(
+            marks.apply("2,11", Emphasis::Added, 10),
+            Err(RangeError::PastEnd(11, 10))
+        );
+        // Nothing of a refused spec is applied.
+        assert_eq!(marks.get(2), Some(Emphasis::Highlighted));
+        assert_eq!(marks.to_spec(Emphasis::Added), "");
+    }
+
+    #[test]
+    fn replaces_the_lines_of_an_emphasis() {
+        let mut marks = LineMarks::default();
+        marks.apply("1-3,6", Emphasis::Highlighted, 9).unwrap();
+        marks.apply("8", Emphasis::Dimmed, 9).unwrap();
+
+        // The range 6 is removed from the spec and 2-3 shortened.
+        marks.replace("1-2,4", Emphasis::Highlighted, 9).unwrap();
+        assert_eq!(marks.to_spec(Emphasis::Highlighted), "1-2,4");
+        assert_eq!(marks.get(6), None);
+        assert_eq!(marks.get(8), Some(Emphasis::Dimmed));
+
+        assert_eq!(
+            marks.replace("1-12", Emphasis::Highlighted, 9),
+            Err(RangeError::PastEnd(12, 9))
+        );
+        assert_eq!(marks.to_spec(Emphasis::Highlighted), "1-2,4");
+
+        marks.replace("", Emphasis::Highlighted, 9).unwrap();
+        assert_eq!(marks.to_spec(Emphasis::Highlighted), "");
+        assert_eq!(marks.to_spec(Emphasis::Dimmed), "8");
+    }
+
+    #[test]
+    fn reads_specs_by_emphasis() {
+        let specs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(name, spec)| (name.to_string(), spec.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let marks = LineMarks::from_specs(&specs(&[("added", "1-2"), ("dimmed", "3")]), 3);
+        let marks = marks.unwrap();
+        assert_eq!(marks.get(2), Some(Emphasis::Added));
+        assert_eq!(marks.get(3), Some(Emphasis::Dimmed));
+
+        assert_eq!(
+            LineMarks::from_specs(&specs(&[("bold", "1")]), 3),
+            Err("unknown emphasis `bold`".to_string())
+        );
+        assert_eq!(
+            LineMarks::from_specs(&specs(&[("removed", "2-4")]), 3),
+            Err("invalid removed lines: line 4 is past the last line, 3".to_string())
+        );
+    }
+
+    #[test]
+    fn toggles_lines() {
+        let mut marks = LineMarks::default();
+        marks.toggle(2, Emphasis::Added);
+        assert_eq!(marks.get(2), Some(Emphasis::Added));
+
+        marks.toggle(2, Emphasis::Removed);
+        assert_eq!(marks.get(2), Some(Emphasis::Removed));
+
+        marks.toggle(2, Emphasis::Removed);
+        assert!(marks.is_empty());
+    }
+
+    #[test]
+    fn writes_specs_it_reads() {
+        let mut marks = LineMarks::default();
+        for line in [1, 2, 3, 5, 8, 9] {
+            marks.toggle(line, Emphasis::Dimmed);
+        }
+        marks.toggle(4, Emphasis::Added);
+
+        let spec = marks.to_spec(Emphasis::Dimmed);
+        assert_eq!(spec, "1-3,5,8-9");
+        assert_eq!(marks.to_spec(Emphasis::Added), "4");
+        assert_eq!(marks.to_spec(Emphasis::Removed), "");
+
+        let mut read = LineMarks::default();
+        read.apply(&spec, Emphasis::Dimmed, 9).unwrap();
+        read.toggle(4, Emphasis::Added);
+        assert_eq!(read, marks);
+    }
+}
@@ -0,0 +1,95 @@
+//! Rust port of the SyntaxHighlighter brushes.
+//!
+//! Code is turned into a [`TokenStream`] once and every view of it, the
+//! preview as well as the exporters, is rendered from that stream.
+
+pub mod annotations;
+pub mod brushes;
+pub mod detect;
+pub mod diff;
+pub mod export;
+pub mod languages;
+pub mod marks;
+pub mod redact;
+pub mod theme;
+pub mod token;
+
+use brushes::Brush;
+use token::{Token, TokenKind, TokenStream};
+
+const TAB_SIZE: usize = 4;
+
+/// Highlights `code` with `brush` after normalizing it like shCore does.
+pub fn highlight(code: &str, brush: &Brush) -> TokenStream {
+    let code = prepare(code);
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    for found in brush.matches(&code) {
+        if found.start > position {
+            tokens.push(Token::new(TokenKind::Plain, &code[position..found.start]));
+        }
+        tokens.push(Token::new(found.kind, &code[found.start..found.end]));
+        position = found.end;
+    }
+
+    if position < code.len() {
+        tokens.push(Token::new(TokenKind::Plain, &code[position..]));
+    }
+
+    TokenStream::from_tokens(tokens)
+}
+
+/// Removes the common indentation, leading and trailing blank lines and
+/// expands tabs to the next tab stop.
+pub fn prepare(code: &str) -> String {
+    let code = code.replace("\r\n", "\n");
+    let unindented = unindent(&code);
+
+    unindented
+        .trim_matches('\n')
+        .split('\n')
+        .map(expand_tabs)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn unindent(code: &str) -> String {
+    let indent = code
+        .split('\n')
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.chars().take_while(|c| c.is_whitespace()).count())
+        .min()
+        .unwrap_or(0);
+
+    if indent == 0 {
+        return code.to_string();
+    }
+
+    code.split('\n')
+        .map(|line| line.chars().skip(indent).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn expand_tabs(line: &str) -> String {
+    if !line.contains('\t') {
+        return line.to_string();
+    }
+
+    let mut expanded = String::with_capacity(line.len() + TAB_SIZE);
+    let mut column = 0;
+
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = TAB_SIZE - column % TAB_SIZE;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += 1;
+        }
+    }
+
+    expanded
+}
@@ -0,0 +1,211 @@
+use super::marks::Emphasis;
+use super::token::TokenKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn rgb(value: u32) -> Self {
+        Color {
+            r: (value >> 16) as u8,
+            g: (value >> 8) as u8,
+            b: value as u8,
+        }
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Mixes `amount` (0 to 1) of `other` into this color.
+    pub fn mix(self, other: Color, amount: f32) -> Color {
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+
+        Color {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Style {
+    pub color: Color,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl Style {
+    pub const fn plain(color: u32) -> Self {
+        Style {
+            color: Color::rgb(color),
+            bold: false,
+            italic: false,
+        }
+    }
+
+    pub const fn bold(color: u32) -> Self {
+        Style {
+            color: Color::rgb(color),
+            bold: true,
+            italic: false,
+        }
+    }
+
+    pub const fn italic(color: u32) -> Self {
+        Style {
+            color: Color::rgb(color),
+            bold: false,
+            italic: true,
+        }
+    }
+}
+
+/// Colors used by exporters, which can't rely on `SyntaxHighlighter.css`.
+pub struct Theme {
+    pub background: Color,
+    pub line_number: Color,
+    pub added_background: Color,
+    pub removed_background: Color,
+    /// Background of the words which changed within a changed line.
+    pub added_word_background: Color,
+    pub removed_word_background: Color,
+    pub highlighted_background: Color,
+    /// Circled numbers of annotated lines and their legend.
+    pub callout: Style,
+    pub text: Style,
+    pub styles: &'static [(TokenKind, Style)],
+}
+
+impl Theme {
+    pub fn style(&self, kind: TokenKind) -> Style {
+        self.styles
+            .iter()
+            .find(|(styled, _)| *styled == kind)
+            .map(|(_, style)| *style)
+            .unwrap_or(self.text)
+    }
+
+    /// Background of a line marked with `emphasis`, dimmed lines keep theirs.
+    pub fn emphasis_background(&self, emphasis: Emphasis) -> Option<Color> {
+        match emphasis {
+            Emphasis::Highlighted => Some(self.highlighted_background),
+            Emphasis::Dimmed => None,
+            Emphasis::Added => Some(self.added_background),
+            Emphasis::Removed => Some(self.removed_background),
+        }
+    }
+
+    /// `style` faded towards the background, for dimmed lines.
+    pub fn dim(&self, style: Style) -> Style {
+        Style {
+            color: style.color.mix(self.background, 0.6),
+            ..style
+        }
+    }
+
+    /// The style of `kind` on a line marked with `emphasis`.
+    pub fn emphasized_style(&self, kind: TokenKind, emphasis: Option<Emphasis>) -> Style {
+        match emphasis {
+            Some(Emphasis::Dimmed) => self.dim(self.style(kind)),
+            _ => self.style(kind),
+        }
+    }
+}
+
+/// The colors of `SyntaxHighlighter.css` and the styles the brushes inject.
+pub const CLASSIC: Theme = Theme {
+    background: Color::rgb(0xffffff),
+    line_number: Color::rgb(0x5c5c5c),
+    added_background: Color::rgb(0xe6ffed),
+    removed_background: Color::rgb(0xffeef0),
+    added_word_background: Color::rgb(0xacf2bd),
+    removed_word_background: Color::rgb(0xfdb8c0),
+    highlighted_background: Color::rgb(0xfff5b1),
+    callout: Style::bold(0x1976d2),
+    text: Style::plain(0x000000),
+    styles: &[
+        (TokenKind::Comment, Style::plain(0x008200)),
+        (TokenKind::String, Style::plain(0x0000ff)),
+        (TokenKind::Keyword, Style::bold(0x006699)),
+        (TokenKind::Preprocessor, Style::plain(0x808080)),
+        (TokenKind::Datatype, Style::bold(0x2e8b57)),
+        (TokenKind::Number, Style::plain(0xc00000)),
+        (TokenKind::Annotation, Style::plain(0x646464)),
+        (TokenKind::Directive, Style::plain(0x008284)),
+        (TokenKind::Variable, Style::plain(0xdd0000)),
+        (TokenKind::Function, Style::plain(0xff1493)),
+        (TokenKind::Operator, Style::plain(0x808080)),
+        (TokenKind::Symbol, Style::plain(0xaa7700)),
+        (TokenKind::Builtin, Style::plain(0xff1493)),
+        (TokenKind::Decorator, Style::plain(0x808080)),
+        (TokenKind::Special, Style::plain(0x8a2be2)),
+        (TokenKind::Value, Style::plain(0x000000)),
+        (TokenKind::Important, Style::bold(0xff0000)),
+        (TokenKind::CData, Style::plain(0xff1493)),
+        (TokenKind::Tag, Style::bold(0x006699)),
+        (TokenKind::TagName, Style::bold(0x006699)),
+        (TokenKind::Attribute, Style::plain(0xff0000)),
+        (TokenKind::AttributeValue, Style::plain(0x0000ff)),
+    ],
+};
+
+/// Shades of gray for printers, telling tokens apart by weight and slant.
+pub const GRAYSCALE: Theme = Theme {
+    background: Color::rgb(0xffffff),
+    line_number: Color::rgb(0x808080),
+    added_background: Color::rgb(0xf0f0f0),
+    removed_background: Color::rgb(0xe0e0e0),
+    added_word_background: Color::rgb(0xd0d0d0),
+    removed_word_background: Color::rgb(0xc0c0c0),
+    highlighted_background: Color::rgb(0xe8e8e8),
+    callout: Style::bold(0x000000),
+    text: Style::plain(0x000000),
+    styles: &[
+        (TokenKind::Comment, Style::italic(0x707070)),
+        (TokenKind::String, Style::plain(0x505050)),
+        (TokenKind::Keyword, Style::bold(0x000000)),
+        (TokenKind::Preprocessor, Style::plain(0x606060)),
+        (TokenKind::Datatype, Style::bold(0x404040)),
+        (TokenKind::Number, Style::plain(0x404040)),
+        (TokenKind::Annotation, Style::italic(0x606060)),
+        (TokenKind::Directive, Style::plain(0x606060)),
+        (TokenKind::Variable, Style::plain(0x202020)),
+        (TokenKind::Function, Style::plain(0x202020)),
+        (TokenKind::Operator, Style::plain(0x606060)),
+        (TokenKind::Symbol, Style::plain(0x404040)),
+        (TokenKind::Builtin, Style::plain(0x202020)),
+        (TokenKind::Decorator, Style::italic(0x606060)),
+        (TokenKind::Special, Style::plain(0x404040)),
+        (TokenKind::Value, Style::plain(0x000000)),
+        (TokenKind::Important, Style::bold(0x000000)),
+        (TokenKind::CData, Style::plain(0x404040)),
+        (TokenKind::Tag, Style::bold(0x000000)),
+        (TokenKind::TagName, Style::bold(0x000000)),
+        (TokenKind::Attribute, Style::plain(0x404040)),
+        (TokenKind::AttributeValue, Style::plain(0x505050)),
+    ],
+};
+
+/// Every theme under the name it is chosen by.
+pub const THEMES: [(&str, &Theme); 2] = [("classic", &CLASSIC), ("grayscale", &GRAYSCALE)];
+
+/// The theme named `name`, ignoring case.
+pub fn find(name: &str) -> Result<&'static Theme, String> {
+    THEMES
+        .into_iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        .map(|(_, theme)| theme)
+        .ok_or_else(|| {
+            let known = THEMES.map(|(name, _)| name);
+            format!(
+                "unknown theme `{name}`, expected one of {}",
+                known.join(", ")
+            )
+        })
+}
@@ -0,0 +1,141 @@
+use serde_derive::{Deserialize, Serialize};
+
+/// Classification of a piece of highlighted code, mirroring the css classes
+/// the SyntaxHighlighter brushes assign to their matches.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Plain,
+    Comment,
+    String,
+    Keyword,
+    Preprocessor,
+    Datatype,
+    Number,
+    Annotation,
+    Directive,
+    Variable,
+    Function,
+    Operator,
+    Symbol,
+    Builtin,
+    Decorator,
+    Special,
+    Value,
+    Important,
+    CData,
+    Tag,
+    TagName,
+    Attribute,
+    AttributeValue,
+}
+
+impl TokenKind {
+    /// The class used in `SyntaxHighlighter.css` for this kind, `None` for plain text.
+    pub fn css_class(self) -> Option<&'static str> {
+        match self {
+            TokenKind::Plain => None,
+            TokenKind::Comment => Some("comment"),
+            TokenKind::String => Some("string"),
+            TokenKind::Keyword => Some("keyword"),
+            TokenKind::Preprocessor => Some("preprocessor"),
+            TokenKind::Datatype => Some("datatypes"),
+            TokenKind::Number => Some("number"),
+            TokenKind::Annotation => Some("annotation"),
+            TokenKind::Directive => Some("directive"),
+            TokenKind::Variable => Some("vars"),
+            TokenKind::Function => Some("func"),
+            TokenKind::Operator => Some("op"),
+            TokenKind::Symbol => Some("symbol"),
+            TokenKind::Builtin => Some("builtin"),
+            TokenKind::Decorator => Some("decorator"),
+            TokenKind::Special => Some("special"),
+            TokenKind::Value => Some("value"),
+            TokenKind::Important => Some("important"),
+            TokenKind::CData => Some("cdata"),
+            TokenKind::Tag => Some("tag"),
+            TokenKind::TagName => Some("tag-name"),
+            TokenKind::Attribute => Some("attribute"),
+            TokenKind::AttributeValue => Some("attribute-value"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+}
+
+impl Token {
+    pub fn new(kind: TokenKind, text: &str) -> Self {
+        Token {
+            kind,
+            text: text.to_string(),
+        }
+    }
+}
+
+/// A single line of highlighted code, never containing a line break.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Line {
+    pub tokens: Vec<Token>,
+}
+
+impl Line {
+    pub fn text(&self) -> String {
+        self.tokens
+            .iter()
+            .map(|token| token.text.as_str())
+            .collect()
+    }
+}
+
+/// The highlighted document every renderer and exporter works from.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenStream {
+    pub lines: Vec<Line>,
+}
+
+impl TokenStream {
+    /// Splits a flat list of tokens into lines, breaking multi-line tokens
+    /// such as block comments into one token per line.
+    pub fn from_tokens(tokens: Vec<Token>) -> Self {
+        let mut lines = vec![Line::default()];
+
+        for token in tokens {
+            let mut parts = token.text.split('\n');
+
+            if let Some(first) = parts.next() {
+                push_token(lines.last_mut().unwrap(), token.kind, first);
+            }
+
+            for part in parts {
+                let mut line = Line::default();
+                push_token(&mut line, token.kind, part);
+                lines.push(line);
+            }
+        }
+
+        TokenStream { lines }
+    }
+
+    /// The plain text of the whole document, lines joined with `\n`.
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(Line::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn push_token(line: &mut Line, kind: TokenKind, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+
+    match line.tokens.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(text),
+        _ => line.tokens.push(Token::new(kind, text)),
+    }
+}
@@ -0,0 +1,5 @@
+//! The highlighting engine, shared by the web app and the native server.
+
+#![deny(clippy::all)]
+
+pub mod engine;
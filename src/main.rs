@@ -1,9 +1,12 @@
 #![deny(clippy::all)]
 
 mod app;
+mod components;
 
 pub mod utils;
 
+use planetb_syntax_highligher::engine;
+
 pub fn main() {
     wasm_logger::init(wasm_logger::Config::default());
     yew::Renderer::<app::App>::new().render();
@@ -0,0 +1,107 @@
+//! Raster images of code, drawn from a [`Scene`] onto a canvas.
+
+use std::f64::consts::TAU;
+
+use js_sys::Promise;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, CanvasRenderingContext2d, HtmlCanvasElement};
+
+use crate::engine::export::html::FONT_FAMILY;
+use crate::engine::export::scene::{Scene, Shape, SHADOW_BLUR, SHADOW_COLOR, SHADOW_OFFSET};
+use crate::engine::theme::Style;
+
+/// Pixel densities images can be saved at.
+pub const SCALES: [u32; 3] = [1, 2, 3];
+
+fn font(style: &Style, size: f64) -> String {
+    format!(
+        "{}{}{size}px {FONT_FAMILY}",
+        if style.italic { "italic " } else { "" },
+        if style.bold { "bold " } else { "" },
+    )
+}
+
+/// Draws `scene` onto a new canvas with `scale` pixels per px.
+pub fn draw(scene: &Scene, scale: u32) -> Result<HtmlCanvasElement, JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?;
+    let canvas: HtmlCanvasElement = document.create_element("canvas")?.dyn_into()?;
+    canvas.set_width((scene.width * scale as f64).ceil() as u32);
+    canvas.set_height((scene.height * scale as f64).ceil() as u32);
+
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or("no 2d context")?
+        .dyn_into()?;
+    context.scale(scale as f64, scale as f64)?;
+    context.set_text_baseline("alphabetic");
+
+    for shape in &scene.shapes {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                radius,
+                color,
+                shadow,
+            } => {
+                context.save();
+                if *shadow {
+                    // Shadows are given in canvas pixels, unaffected by the scale.
+                    context.set_shadow_blur(SHADOW_BLUR * scale as f64);
+                    context.set_shadow_offset_y(SHADOW_OFFSET * scale as f64);
+                    context.set_shadow_color(SHADOW_COLOR);
+                }
+                context.set_fill_style_str(&color.to_hex());
+                context.begin_path();
+                context.round_rect_with_f64(*x, *y, *width, *height, *radius)?;
+                context.fill();
+                context.restore();
+            }
+            Shape::Circle {
+                x,
+                y,
+                radius,
+                color,
+            } => {
+                context.set_fill_style_str(&color.to_hex());
+                context.begin_path();
+                context.arc(*x, *y, *radius, 0.0, TAU)?;
+                context.fill();
+            }
+            Shape::Text { y, runs } => {
+                for run in runs {
+                    context.set_font(&font(&run.style, scene.font_size));
+                    context.set_fill_style_str(&run.style.color.to_hex());
+                    context.fill_text(&run.text, run.x, *y)?;
+                }
+            }
+        }
+    }
+
+    Ok(canvas)
+}
+
+/// Encodes what is drawn on `canvas` as PNG.
+pub async fn png(canvas: &HtmlCanvasElement) -> Result<Blob, JsValue> {
+    let mut result = Ok(());
+    let promise = Promise::new(&mut |resolve, reject| {
+        let on_blob = Closure::once_into_js(move |blob: JsValue| {
+            if blob.is_null() {
+                let _ = reject.call1(&JsValue::NULL, &"the image is too large".into());
+            } else {
+                let _ = resolve.call1(&JsValue::NULL, &blob);
+            }
+        });
+
+        result = canvas.to_blob_with_type(on_blob.unchecked_ref(), "image/png");
+    });
+    result?;
+
+    Ok(JsFuture::from(promise).await?.unchecked_into())
+}
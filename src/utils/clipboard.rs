@@ -0,0 +1,58 @@
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, ClipboardEvent, ClipboardItem, HtmlDocument};
+
+/// Puts `html` on the clipboard together with a plain `text` fallback.
+///
+/// Hooks the `copy` event around `document.execCommand("copy")`, the async
+/// clipboard api can't write `text/html` in every browser yet.
+pub fn copy_rich_text(html: &str, text: &str) -> bool {
+    let window = web_sys::window().expect("no global `window` exists");
+    let document = window.document().expect("should have a document on window");
+
+    let html = html.to_string();
+    let text = text.to_string();
+    let on_copy = Closure::wrap(Box::new(move |event: ClipboardEvent| {
+        if let Some(data) = event.clipboard_data() {
+            let _ = data.set_data("text/html", &html);
+            let _ = data.set_data("text/plain", &text);
+            event.prevent_default();
+        }
+    }) as Box<dyn FnMut(ClipboardEvent)>);
+
+    document
+        .add_event_listener_with_callback("copy", on_copy.as_ref().unchecked_ref())
+        .unwrap();
+
+    let copied = document
+        .dyn_ref::<HtmlDocument>()
+        .and_then(|document| document.exec_command("copy").ok())
+        .unwrap_or(false);
+
+    document
+        .remove_event_listener_with_callback("copy", on_copy.as_ref().unchecked_ref())
+        .unwrap();
+
+    copied
+}
+
+/// Puts the image `blob` on the clipboard, which only the async clipboard api
+/// can do.
+pub async fn copy_image(blob: &Blob) -> Result<(), wasm_bindgen::JsValue> {
+    let window = web_sys::window().expect("no global `window` exists");
+
+    let items = js_sys::Object::new();
+    js_sys::Reflect::set(&items, &blob.type_().into(), blob)?;
+    let item = ClipboardItem::new_with_record_from_str_to_blob_promise(&items)?;
+
+    JsFuture::from(
+        window
+            .navigator()
+            .clipboard()
+            .write(&js_sys::Array::of1(&item)),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+/// Offers `content` as a file download named `file_name`.
+pub fn download(file_name: &str, mime_type: &str, content: &str) {
+    let parts = js_sys::Array::of1(&JsValue::from_str(content));
+    let options = BlobPropertyBag::new();
+    options.set_type(mime_type);
+
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options).unwrap();
+    download_blob(file_name, &blob);
+}
+
+/// Offers binary `content` as a file download named `file_name`.
+pub fn download_bytes(file_name: &str, mime_type: &str, content: &[u8]) {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(content));
+    let options = BlobPropertyBag::new();
+    options.set_type(mime_type);
+
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options).unwrap();
+    download_blob(file_name, &blob);
+}
+
+/// Offers `blob` as a file download named `file_name`.
+pub fn download_blob(file_name: &str, blob: &Blob) {
+    let window = web_sys::window().expect("no global `window` exists");
+    let document = window.document().expect("should have a document on window");
+
+    let url = Url::create_object_url_with_blob(blob).unwrap();
+
+    let anchor: HtmlAnchorElement = document.create_element("a").unwrap().dyn_into().unwrap();
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    Url::revoke_object_url(&url).unwrap();
+}
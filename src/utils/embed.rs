@@ -0,0 +1,111 @@
+//! The protocol of the app embedded in an iframe, opened with `?embed`. The
+//! embedding page posts
+//!
+//! ```js
+//! { type: "highlight", code, language, theme, format, id }
+//! ```
+//!
+//! with everything but `code` optional, and the app shows the code and posts
+//! `{ type: "highlighted", id, format, content }` or `{ type: "error", id,
+//! message }` back. `{ type: "ready" }` tells the page when to start.
+//!
+//! Only the origins listed in `EMBED_ORIGINS` when building, separated by
+//! commas, are listened and answered to. `*` stands for any origin.
+
+use serde_derive::Deserialize;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::MessageEvent;
+use yew::Callback;
+
+const ORIGINS: &str = match option_env!("EMBED_ORIGINS") {
+    Some(origins) => origins,
+    None => "",
+};
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Request {
+    Highlight(Highlight),
+}
+
+#[derive(Deserialize)]
+pub struct Highlight {
+    pub code: String,
+    /// An alias of a brush, e.g. `python` or `py`, guessed if missing.
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// The extension of a text export format, `html` if missing.
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Sent back with the answer, to tell answers apart.
+    #[serde(default)]
+    pub id: Option<serde_json::Value>,
+}
+
+fn origins() -> impl Iterator<Item = &'static str> {
+    ORIGINS
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+}
+
+fn allows(origin: &str) -> bool {
+    origins().any(|allowed| allowed == "*" || allowed == origin)
+}
+
+/// Whether the app was opened to be embedded.
+pub fn is_embedded() -> bool {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .is_some_and(|search| {
+            search
+                .trim_start_matches('?')
+                .split('&')
+                .any(|parameter| parameter == "embed" || parameter.starts_with("embed="))
+        })
+}
+
+/// Calls `on_request` with every request of an allowed origin and that
+/// origin, and announces to the embedding page that requests are welcome.
+pub fn listen(on_request: Callback<(Request, String)>) {
+    let window = web_sys::window().expect("no global `window` exists");
+
+    let on_message = Closure::<dyn Fn(MessageEvent)>::new(move |event: MessageEvent| {
+        let origin = event.origin();
+        if !allows(&origin) {
+            return;
+        }
+
+        let request = js_sys::JSON::stringify(&event.data())
+            .ok()
+            .and_then(|json| json.as_string())
+            .and_then(|json| serde_json::from_str::<Request>(&json).ok());
+        if let Some(request) = request {
+            on_request.emit((request, origin));
+        }
+    });
+    window
+        .add_event_listener_with_callback("message", on_message.as_ref().unchecked_ref())
+        .unwrap();
+    // Listens as long as the app runs.
+    on_message.forget();
+
+    for origin in origins() {
+        reply(origin, &serde_json::json!({ "type": "ready" }));
+    }
+}
+
+/// Posts `message` to the embedding page, if it's at `origin`.
+pub fn reply(origin: &str, message: &serde_json::Value) {
+    let Some(parent) = web_sys::window().and_then(|window| window.parent().ok().flatten()) else {
+        return;
+    };
+
+    let message = js_sys::JSON::parse(&message.to_string()).unwrap_or(JsValue::NULL);
+    if let Err(error) = parent.post_message(&message, origin) {
+        log::debug!("Answering the embedding page failed: {:?}", error);
+    }
+}
@@ -0,0 +1,123 @@
+//! Recently highlighted code, kept in the local storage so a paste doesn't
+//! lose what was there before.
+
+use gloo_storage::{LocalStorage, Storage};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::engine::annotations::Annotations;
+use crate::engine::marks::LineMarks;
+
+pub const HISTORY_KEY: &str = "codestyle.history";
+/// The oldest sessions are dropped once there are more than this many...
+pub const MAX_SESSIONS: usize = 20;
+/// ...or once their code takes up more bytes than this.
+pub const MAX_BYTES: usize = 512 * 1024;
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Session {
+    /// When the session started, in milliseconds since the epoch.
+    pub id: f64,
+    pub updated: f64,
+    /// `css_class` of the programming language.
+    pub language: Option<String>,
+    pub code: String,
+    #[serde(default)]
+    pub marks: LineMarks,
+    #[serde(default)]
+    pub annotations: Annotations,
+}
+
+impl Session {
+    /// The first line with content, shortened to fit a menu entry.
+    pub fn title(&self) -> String {
+        let line = self
+            .code
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default();
+
+        if line.chars().count() > 40 {
+            format!("{}…", line.chars().take(40).collect::<String>())
+        } else {
+            line.to_string()
+        }
+    }
+}
+
+/// The recent sessions, the most recently updated first.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+pub struct History {
+    sessions: Vec<Session>,
+}
+
+impl History {
+    pub fn load() -> Self {
+        LocalStorage::get(HISTORY_KEY).unwrap_or_default()
+    }
+
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Stores `session`, replacing the earlier state of the same session.
+    pub fn record(&mut self, session: Session) {
+        self.sessions.retain(|recorded| recorded.id != session.id);
+        self.sessions.insert(0, session);
+        self.trim();
+        self.save();
+    }
+
+    /// Adds the sessions of `other`, keeping the newer state of sessions
+    /// known to both.
+    pub fn merge(&mut self, other: History) {
+        for session in other.sessions {
+            match self
+                .sessions
+                .iter_mut()
+                .find(|recorded| recorded.id == session.id)
+            {
+                Some(recorded) if recorded.updated < session.updated => *recorded = session,
+                Some(_) => {}
+                None => self.sessions.push(session),
+            }
+        }
+
+        self.sessions
+            .sort_by(|a, b| b.updated.total_cmp(&a.updated));
+        self.trim();
+        self.save();
+    }
+
+    /// Stores `self` in place of whatever history was kept before.
+    pub fn save(&self) {
+        if let Err(error) = LocalStorage::set(HISTORY_KEY, self) {
+            log::debug!("Storing the history failed: {}", error);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.sessions.clear();
+        LocalStorage::delete(HISTORY_KEY);
+    }
+
+    fn trim(&mut self) {
+        let mut bytes = 0;
+        let mut kept = 0;
+
+        for session in &self.sessions {
+            bytes += session.code.len();
+            // The newest session is always kept, however large it is.
+            if kept > 0 && (kept == MAX_SESSIONS || bytes > MAX_BYTES) {
+                break;
+            }
+            kept += 1;
+        }
+
+        self.sessions.truncate(kept);
+    }
+}
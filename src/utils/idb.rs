@@ -0,0 +1,142 @@
+//! Just enough of IndexedDB to keep records in object stores, with requests
+//! turned into futures.
+
+use js_sys::{Array, Promise};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    IdbDatabase, IdbObjectStore, IdbObjectStoreParameters, IdbRequest, IdbTransaction,
+    IdbTransactionMode,
+};
+
+/// Opens the database `name`, creating every missing store of `stores` with
+/// auto incremented keys in the `id` property of its records.
+pub async fn open(
+    name: &str,
+    version: u32,
+    stores: &'static [&'static str],
+) -> Result<IdbDatabase, JsValue> {
+    let factory = web_sys::window()
+        .expect("no global `window` exists")
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from_str("IndexedDB is not available"))?;
+    let request = factory.open_with_u32(name, version)?;
+
+    let upgrading = request.clone();
+    let on_upgrade_needed = Closure::once_into_js(move || {
+        let Ok(database) = upgrading.result() else {
+            return;
+        };
+        let database: IdbDatabase = database.unchecked_into();
+        let existing = database.object_store_names();
+
+        for store in stores {
+            if !existing.contains(store) {
+                let parameters = IdbObjectStoreParameters::new();
+                parameters.set_key_path(&JsValue::from_str("id"));
+                parameters.set_auto_increment(true);
+
+                let _ = database.create_object_store_with_optional_parameters(store, &parameters);
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+
+    Ok(wait(&request).await?.unchecked_into())
+}
+
+fn store(
+    database: &IdbDatabase,
+    name: &str,
+    mode: IdbTransactionMode,
+) -> Result<IdbObjectStore, JsValue> {
+    database
+        .transaction_with_str_and_mode(name, mode)?
+        .object_store(name)
+}
+
+/// Every record of the store `name`, ordered by key.
+pub async fn get_all(database: &IdbDatabase, name: &str) -> Result<Array, JsValue> {
+    let request = store(database, name, IdbTransactionMode::Readonly)?.get_all()?;
+
+    Ok(wait(&request).await?.unchecked_into())
+}
+
+/// Inserts or replaces `record`, resolving to its key.
+pub async fn put(database: &IdbDatabase, name: &str, record: &JsValue) -> Result<JsValue, JsValue> {
+    let request = store(database, name, IdbTransactionMode::Readwrite)?.put(record)?;
+
+    wait(&request).await
+}
+
+pub async fn delete(database: &IdbDatabase, name: &str, key: &JsValue) -> Result<(), JsValue> {
+    let request = store(database, name, IdbTransactionMode::Readwrite)?.delete(key)?;
+
+    wait(&request).await.map(|_| ())
+}
+
+/// Inserts or replaces every record of `records` in one transaction, after
+/// removing every record of the store `name` first if `clear` is set. Should
+/// any write fail, the store is left as it was.
+pub async fn put_all(
+    database: &IdbDatabase,
+    name: &str,
+    records: &[JsValue],
+    clear: bool,
+) -> Result<(), JsValue> {
+    let transaction =
+        database.transaction_with_str_and_mode(name, IdbTransactionMode::Readwrite)?;
+    let store = transaction.object_store(name)?;
+
+    let queued = (|| {
+        if clear {
+            store.clear()?;
+        }
+        for record in records {
+            store.put(record)?;
+        }
+        Ok(())
+    })();
+    if let Err(error) = queued {
+        let _ = transaction.abort();
+        return Err(error);
+    }
+
+    committed(&transaction).await
+}
+
+/// Resolves once `transaction` is committed, failing if it was aborted.
+async fn committed(transaction: &IdbTransaction) -> Result<(), JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let on_complete = Closure::once_into_js(move || {
+            let _ = resolve.call0(&JsValue::NULL);
+        });
+        let on_abort = Closure::once_into_js(move |event: JsValue| {
+            let _ = reject.call1(&JsValue::NULL, &event);
+        });
+
+        transaction.set_oncomplete(Some(on_complete.unchecked_ref()));
+        transaction.set_onabort(Some(on_abort.unchecked_ref()));
+    });
+
+    JsFuture::from(promise).await.map(|_| ())
+}
+
+/// Resolves to the result of `request` once it succeeded.
+async fn wait(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let succeeded = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let _ = resolve.call1(&JsValue::NULL, &succeeded.result().unwrap_or_default());
+        });
+        let on_error = Closure::once_into_js(move |event: JsValue| {
+            let _ = reject.call1(&JsValue::NULL, &event);
+        });
+
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+
+    JsFuture::from(promise).await
+}
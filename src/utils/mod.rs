@@ -1,2 +1,9 @@
-pub mod highlighter;
+pub mod canvas;
+pub mod clipboard;
+pub mod download;
+pub mod embed;
+pub mod history;
+pub mod idb;
 pub mod images;
+pub mod snippets;
+pub mod workspace;
@@ -0,0 +1,123 @@
+//! Named and tagged snippets kept in IndexedDB.
+
+use serde_derive::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::engine::annotations::Annotations;
+use crate::engine::marks::LineMarks;
+use crate::utils::idb;
+
+pub const DATABASE: &str = "codestyle";
+pub const DATABASE_VERSION: u32 = 1;
+pub const STORE: &str = "snippets";
+/// Every store of [`DATABASE`], missing ones are created when it is opened.
+pub const STORES: &[&str] = &[STORE];
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Snippet {
+    /// Assigned by IndexedDB when the snippet is first saved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
+    pub name: String,
+    pub tags: Vec<String>,
+    /// `css_class` of the programming language.
+    pub language: Option<String>,
+    pub code: String,
+    #[serde(default)]
+    pub marks: LineMarks,
+    #[serde(default)]
+    pub annotations: Annotations,
+    /// Milliseconds since the epoch.
+    pub created: f64,
+    pub updated: f64,
+}
+
+impl Snippet {
+    /// Whether every word of `query` is part of the name, a tag or the language.
+    pub fn matches(&self, query: &str) -> bool {
+        let haystack = format!(
+            "{} {} {}",
+            self.name,
+            self.tags.join(" "),
+            self.language.as_deref().unwrap_or_default()
+        )
+        .to_lowercase();
+
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| haystack.contains(word))
+    }
+
+    /// An unsaved copy with fresh timestamps.
+    pub fn duplicate(&self) -> Snippet {
+        let now = js_sys::Date::now();
+
+        Snippet {
+            id: None,
+            name: format!("{} (copy)", self.name),
+            created: now,
+            updated: now,
+            ..self.clone()
+        }
+    }
+}
+
+/// Splits a comma separated list of tags, dropping empty ones.
+pub fn parse_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn to_js(snippet: &Snippet) -> Result<JsValue, JsValue> {
+    let json = serde_json::to_string(snippet).map_err(|error| error.to_string())?;
+
+    js_sys::JSON::parse(&json)
+}
+
+fn from_js(value: &JsValue) -> Result<Snippet, JsValue> {
+    let json = String::from(js_sys::JSON::stringify(value)?);
+
+    serde_json::from_str(&json).map_err(|error| error.to_string().into())
+}
+
+/// Every saved snippet, the most recently updated first.
+pub async fn load_all() -> Result<Vec<Snippet>, JsValue> {
+    let database = idb::open(DATABASE, DATABASE_VERSION, STORES).await?;
+    let mut snippets = idb::get_all(&database, STORE)
+        .await?
+        .iter()
+        .map(|value| from_js(&value))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    snippets.sort_by(|a, b| b.updated.total_cmp(&a.updated));
+    Ok(snippets)
+}
+
+/// Inserts `snippet`, or replaces it if it has an `id`, and returns its `id`.
+pub async fn save(snippet: &Snippet) -> Result<u32, JsValue> {
+    let database = idb::open(DATABASE, DATABASE_VERSION, STORES).await?;
+    let key = idb::put(&database, STORE, &to_js(snippet)?).await?;
+
+    key.as_f64()
+        .map(|key| key as u32)
+        .ok_or_else(|| JsValue::from_str("snippet key is not a number"))
+}
+
+/// Saves every snippet of `snippets` at once, in place of every stored one if
+/// `replace` is set. Nothing is changed unless all of them are saved.
+pub async fn save_all(snippets: &[Snippet], replace: bool) -> Result<(), JsValue> {
+    let records = snippets.iter().map(to_js).collect::<Result<Vec<_>, _>>()?;
+    let database = idb::open(DATABASE, DATABASE_VERSION, STORES).await?;
+
+    idb::put_all(&database, STORE, &records, replace).await
+}
+
+pub async fn delete(id: u32) -> Result<(), JsValue> {
+    let database = idb::open(DATABASE, DATABASE_VERSION, STORES).await?;
+
+    idb::delete(&database, STORE, &JsValue::from(id)).await
+}
@@ -0,0 +1,297 @@
+//! Everything the app keeps in the browser bundled into one JSON file, so it
+//! can be moved to another machine.
+
+use std::fmt;
+
+use gloo_storage::{LocalStorage, Storage};
+use serde_derive::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::app::STATE_KEY;
+use crate::utils::history::History;
+use crate::utils::snippets::{self, Snippet};
+
+/// Tells bundles apart from any other JSON file.
+pub const BUNDLE_FORMAT: &str = "codestyle-workspace";
+/// Raised whenever a store is added to or changed in the bundle. Bundles of
+/// older versions are still read, stores they lack are left alone.
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    /// Milliseconds since the epoch.
+    pub exported: f64,
+    /// Whatever is stored under [`STATE_KEY`], kept as is.
+    #[serde(default)]
+    pub settings: Option<serde_json::Value>,
+    #[serde(default)]
+    pub history: History,
+    #[serde(default)]
+    pub snippets: Vec<Snippet>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BundleError {
+    Json(String),
+    Format,
+    Version(u32),
+    UnnamedSnippet,
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleError::Json(error) => write!(f, "the file is not valid JSON: {error}"),
+            BundleError::Format => write!(f, "the file is not a workspace export"),
+            BundleError::Version(version) => write!(
+                f,
+                "the workspace was exported by a newer version (format {version}, this app reads up to {BUNDLE_VERSION})"
+            ),
+            BundleError::UnnamedSnippet => write!(f, "the workspace contains a snippet without a name"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Keeps what is stored and adds the bundle to it.
+    Merge,
+    /// Drops what is stored in favor of the bundle.
+    Replace,
+}
+
+/// Reads and validates a bundle.
+pub fn parse(json: &str) -> Result<Bundle, BundleError> {
+    let value: serde_json::Value =
+        serde_json::from_str(json).map_err(|error| BundleError::Json(error.to_string()))?;
+
+    if value.get("format").and_then(|format| format.as_str()) != Some(BUNDLE_FORMAT) {
+        return Err(BundleError::Format);
+    }
+    match value.get("version").and_then(|version| version.as_u64()) {
+        Some(version) if version > BUNDLE_VERSION as u64 => {
+            return Err(BundleError::Version(version as u32))
+        }
+        Some(_) => {}
+        None => return Err(BundleError::Format),
+    }
+
+    let bundle: Bundle =
+        serde_json::from_value(value).map_err(|error| BundleError::Json(error.to_string()))?;
+
+    if bundle
+        .snippets
+        .iter()
+        .any(|snippet| snippet.name.trim().is_empty())
+    {
+        return Err(BundleError::UnnamedSnippet);
+    }
+
+    Ok(bundle)
+}
+
+/// Names of the snippets of `bundle` which differ from a stored snippet of the
+/// same name. Merging imports them under a new name.
+pub fn conflicts(bundle: &Bundle, stored: &[Snippet]) -> Vec<String> {
+    bundle
+        .snippets
+        .iter()
+        .filter(|snippet| {
+            stored
+                .iter()
+                .any(|known| known.name == snippet.name && !same_content(known, snippet))
+        })
+        .map(|snippet| snippet.name.clone())
+        .collect()
+}
+
+fn same_content(a: &Snippet, b: &Snippet) -> bool {
+    a.language == b.language
+        && a.code == b.code
+        && a.tags == b.tags
+        && a.marks == b.marks
+        && a.annotations == b.annotations
+}
+
+/// Collects every store into a bundle.
+pub async fn export() -> Result<String, JsValue> {
+    let bundle = Bundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported: js_sys::Date::now(),
+        settings: LocalStorage::get(STATE_KEY).ok(),
+        history: History::load(),
+        snippets: snippets::load_all().await?,
+    };
+
+    serde_json::to_string_pretty(&bundle).map_err(|error| error.to_string().into())
+}
+
+/// The snippets of `bundle` to save next to `stored`, without those stored
+/// already and with a new name for those whose name is taken, and the new
+/// names.
+fn additions(bundle: &Bundle, stored: &[Snippet]) -> (Vec<Snippet>, Vec<String>) {
+    let mut renamed = Vec::new();
+    let snippets = bundle
+        .snippets
+        .iter()
+        .filter(|snippet| {
+            !stored
+                .iter()
+                .any(|known| known.name == snippet.name && same_content(known, snippet))
+        })
+        .map(|snippet| {
+            let mut snippet = Snippet {
+                id: None,
+                ..snippet.clone()
+            };
+            if stored.iter().any(|known| known.name == snippet.name) {
+                snippet.name = format!("{} (imported)", snippet.name);
+                renamed.push(snippet.name.clone());
+            }
+            snippet
+        })
+        .collect();
+
+    (snippets, renamed)
+}
+
+/// Writes `bundle` into every store, returning a summary of what changed. The
+/// snippets are written in one transaction, so a failed import keeps the
+/// stored ones even when replacing them.
+pub async fn import(bundle: Bundle, mode: ImportMode) -> Result<String, JsValue> {
+    let stored = match mode {
+        ImportMode::Merge => snippets::load_all().await?,
+        ImportMode::Replace => Vec::new(),
+    };
+    let (additions, renamed) = additions(&bundle, &stored);
+    snippets::save_all(&additions, mode == ImportMode::Replace).await?;
+    let added = additions.len();
+
+    let sessions = bundle.history.sessions().len();
+    match mode {
+        ImportMode::Merge => History::load().merge(bundle.history),
+        ImportMode::Replace => History::default().merge(bundle.history),
+    }
+
+    if let Some(settings) = bundle.settings {
+        if mode == ImportMode::Replace || LocalStorage::raw().get_item(STATE_KEY)?.is_none() {
+            LocalStorage::set(STATE_KEY, settings).map_err(|error| error.to_string())?;
+        }
+    }
+
+    let mut summary = format!("Imported {added} snippets and {sessions} recent sessions.");
+    if !renamed.is_empty() {
+        summary.push_str(&format!(
+            " Renamed to avoid conflicts: {}.",
+            renamed.join(", ")
+        ));
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(name: &str, code: &str) -> Snippet {
+        Snippet {
+            id: Some(1),
+            name: name.to_string(),
+            tags: Vec::new(),
+            language: Some("python".to_string()),
+            code: code.to_string(),
+            marks: Default::default(),
+            annotations: Default::default(),
+            created: 0.0,
+            updated: 0.0,
+        }
+    }
+
+    fn bundle(version: u32, snippets: Vec<Snippet>) -> String {
+        serde_json::to_string(&Bundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version,
+            exported: 0.0,
+            settings: None,
+            history: History::default(),
+            snippets,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn reads_bundles_of_this_and_older_versions() {
+        let snippets = vec![snippet("a", "x = 1")];
+
+        let read = parse(&bundle(BUNDLE_VERSION, snippets.clone())).unwrap();
+        assert_eq!(read.snippets, snippets);
+        assert!(parse(&bundle(0, Vec::new())).is_ok());
+        // Stores of later versions are left out of older bundles.
+        let minimal = format!(r#"{{"format": "{BUNDLE_FORMAT}", "version": 1, "exported": 0}}"#);
+        assert_eq!(parse(&minimal).unwrap().snippets, Vec::new());
+    }
+
+    #[test]
+    fn rejects_what_is_no_bundle_of_this_version() {
+        assert!(matches!(parse("{\"format\": "), Err(BundleError::Json(_))));
+        assert_eq!(parse("[1, 2]"), Err(BundleError::Format));
+        assert_eq!(
+            parse(r#"{"format": "other", "version": 1, "exported": 0}"#),
+            Err(BundleError::Format)
+        );
+        assert_eq!(
+            parse(&format!(
+                r#"{{"format": "{BUNDLE_FORMAT}", "exported": 0}}"#
+            )),
+            Err(BundleError::Format)
+        );
+        assert_eq!(
+            parse(&bundle(BUNDLE_VERSION + 1, Vec::new())),
+            Err(BundleError::Version(BUNDLE_VERSION + 1))
+        );
+        assert!(matches!(
+            parse(&format!(
+                r#"{{"format": "{BUNDLE_FORMAT}", "version": 1, "exported": 0, "snippets": [{{"name": "a"}}]}}"#
+            )),
+            Err(BundleError::Json(_))
+        ));
+        assert_eq!(
+            parse(&bundle(BUNDLE_VERSION, vec![snippet(" ", "x")])),
+            Err(BundleError::UnnamedSnippet)
+        );
+    }
+
+    #[test]
+    fn renames_snippets_whose_name_is_taken() {
+        let stored = [snippet("same", "x = 1"), snippet("changed", "x = 1")];
+        let bundle = parse(&bundle(
+            BUNDLE_VERSION,
+            vec![
+                snippet("same", "x = 1"),
+                snippet("changed", "x = 2"),
+                snippet("new", "x = 3"),
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!(conflicts(&bundle, &stored), ["changed"]);
+
+        let (added, renamed) = additions(&bundle, &stored);
+        assert_eq!(
+            added
+                .iter()
+                .map(|snippet| (snippet.name.as_str(), snippet.id))
+                .collect::<Vec<_>>(),
+            [("changed (imported)", None), ("new", None)]
+        );
+        assert_eq!(renamed, ["changed (imported)"]);
+
+        let (added, renamed) = additions(&bundle, &[]);
+        assert_eq!(added.len(), 3);
+        assert!(renamed.is_empty());
+    }
+}